@@ -0,0 +1,7 @@
+-- Remember which task reminders were already delivered
+
+ALTER TABLE reminders ADD COLUMN sent BOOLEAN NOT NULL DEFAULT 0;
+-- Deliveries that reached the user in no way, the reminder is tried again until it's given up on
+ALTER TABLE reminders ADD COLUMN failed_attempts INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_reminders_sent ON reminders(sent);
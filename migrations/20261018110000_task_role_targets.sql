@@ -0,0 +1,10 @@
+-- Tasks assigned to a whole discord role, resolved against the member's roles on every read
+
+CREATE TABLE task_role_targets (
+    task_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    PRIMARY KEY (task_id, role_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_role_targets_role_id ON task_role_targets(role_id);
@@ -0,0 +1,9 @@
+-- Track completion per assignee, tasks.completed now means every assignee is done
+
+ALTER TABLE task_targets ADD COLUMN completed BOOLEAN NOT NULL DEFAULT 0;
+
+-- Rows created for members of an assigned role, they only hold that member's completion
+ALTER TABLE task_targets ADD COLUMN via_role BOOLEAN NOT NULL DEFAULT 0;
+
+UPDATE task_targets
+SET completed = (SELECT completed FROM tasks WHERE tasks.id = task_targets.task_id);
@@ -0,0 +1,5 @@
+-- Board workflow status: 0 = todo, 1 = in progress, 2 = in review, 3 = done
+
+ALTER TABLE tasks ADD COLUMN status INTEGER NOT NULL DEFAULT 0;
+
+UPDATE tasks SET status = 3 WHERE completed = 1;
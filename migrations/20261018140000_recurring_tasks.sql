@@ -0,0 +1,12 @@
+-- Repeat rules, repeat_unit: 0 = days, 1 = weeks, 2 = months
+
+ALTER TABLE tasks ADD COLUMN repeat_interval INTEGER;
+ALTER TABLE tasks ADD COLUMN repeat_unit INTEGER;
+ALTER TABLE tasks ADD COLUMN repeat_until INTEGER;
+
+-- Set once the next occurrence of a repeating task was created
+ALTER TABLE tasks ADD COLUMN repeat_generated BOOLEAN NOT NULL DEFAULT 0;
+
+-- Deadline of the first occurrence, later ones are counted from it so they don't drift.
+-- NULL means the task itself is the first one
+ALTER TABLE tasks ADD COLUMN repeat_anchor INTEGER;
@@ -0,0 +1,13 @@
+-- Ordered checklist steps of a task, done_by is whoever ticked the item
+
+CREATE TABLE checklist_items (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    done_by INTEGER,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (done_by) REFERENCES users(discord_id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_checklist_items_task_id ON checklist_items(task_id);
@@ -0,0 +1,11 @@
+CREATE TABLE task_comments (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    author INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    created_unixtimestamp INTEGER NOT NULL,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (author) REFERENCES users(discord_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_task_comments_task_id ON task_comments(task_id);
@@ -0,0 +1,13 @@
+-- Append-only log of task changes, kept without a foreign key so it outlives deleted tasks
+
+CREATE TABLE task_history (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    actor INTEGER NOT NULL,
+    action INTEGER NOT NULL,
+    before TEXT,
+    after TEXT,
+    created_unixtimestamp INTEGER NOT NULL
+);
+
+CREATE INDEX idx_task_history_task_id ON task_history(task_id);
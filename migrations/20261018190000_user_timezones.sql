@@ -0,0 +1,2 @@
+-- IANA name like "Europe/Warsaw", NULL falls back to the configured guild timezone
+ALTER TABLE users ADD COLUMN timezone TEXT;
@@ -0,0 +1,8 @@
+-- Overdue tasks are announced once, and escalated to the board once if they stay that way
+
+ALTER TABLE tasks ADD COLUMN overdue_notified BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE tasks ADD COLUMN overdue_escalated BOOLEAN NOT NULL DEFAULT 0;
+
+-- tasks already past their deadline would all be announced at once on the first run
+UPDATE tasks SET overdue_notified = 1, overdue_escalated = 1
+WHERE deadline_unixtimestamp < CAST(strftime('%s', 'now') AS INTEGER);
@@ -0,0 +1,39 @@
+-- When tasks were created, assigned and finished, for statistics
+
+ALTER TABLE tasks ADD COLUMN created_unixtimestamp INTEGER;
+ALTER TABLE task_targets ADD COLUMN assigned_unixtimestamp INTEGER;
+ALTER TABLE task_targets ADD COLUMN completed_unixtimestamp INTEGER;
+
+-- Existing rows get their times from the history, as close as it gets.
+-- Tasks older than the history stay NULL and are left out of the lead times.
+
+UPDATE tasks
+SET created_unixtimestamp = (
+    SELECT MIN(created_unixtimestamp) FROM task_history h WHERE h.task_id = tasks.id
+);
+
+UPDATE task_targets
+SET assigned_unixtimestamp = (SELECT created_unixtimestamp FROM tasks WHERE tasks.id = task_targets.task_id);
+
+UPDATE task_targets
+SET completed_unixtimestamp = COALESCE(
+    (
+        SELECT MAX(created_unixtimestamp) FROM task_history h
+        WHERE h.task_id = task_targets.task_id AND h.actor = task_targets.user_id AND h.action = 3
+    ),
+    (SELECT deadline_unixtimestamp FROM tasks WHERE tasks.id = task_targets.task_id)
+)
+WHERE completed = 1;
+
+-- Assignments of deleted tasks, kept without a foreign key so the statistics outlive them
+
+CREATE TABLE task_stats_archive (
+    task_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    deadline_unixtimestamp INTEGER NOT NULL,
+    assigned_unixtimestamp INTEGER,
+    completed_unixtimestamp INTEGER,
+    PRIMARY KEY (task_id, user_id)
+);
+
+CREATE INDEX idx_task_stats_archive_deadline ON task_stats_archive(deadline_unixtimestamp);
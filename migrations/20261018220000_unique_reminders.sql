@@ -0,0 +1,8 @@
+-- A user can set each offset on a task once
+
+DELETE FROM reminders
+WHERE id NOT IN (
+    SELECT MIN(id) FROM reminders GROUP BY task, user_id, when_unixtimestamp
+);
+
+CREATE UNIQUE INDEX idx_reminders_unique ON reminders(task, user_id, when_unixtimestamp);
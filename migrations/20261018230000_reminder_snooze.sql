@@ -0,0 +1,16 @@
+-- A snoozed reminder is sent again at this time instead of its offset before the deadline
+
+ALTER TABLE reminders ADD COLUMN snoozed_until_unixtimestamp INTEGER;
+
+-- Reminders about upcoming events, one per member and occurrence, kept so they can be snoozed
+
+CREATE TABLE event_reminders_sent (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    uid TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    start_unixtimestamp INTEGER NOT NULL,
+    snoozed_until_unixtimestamp INTEGER,
+    UNIQUE (user_id, uid, start_unixtimestamp),
+    FOREIGN KEY (user_id) REFERENCES users(discord_id) ON DELETE CASCADE
+);
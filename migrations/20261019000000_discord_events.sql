@@ -0,0 +1,15 @@
+-- Guild scheduled events made from calendar and custom events, by the calendar source and
+-- the event's uid, with what they were last synced with to tell when they changed.
+-- Custom events have an empty source.
+
+CREATE TABLE discord_events (
+    source TEXT NOT NULL,
+    uid TEXT NOT NULL,
+    guild_id INTEGER NOT NULL,
+    discord_event_id INTEGER NOT NULL,
+    summary TEXT NOT NULL,
+    start_unixtimestamp INTEGER NOT NULL,
+    end_unixtimestamp INTEGER NOT NULL,
+    location TEXT NOT NULL,
+    PRIMARY KEY (source, uid, guild_id)
+);
@@ -84,38 +84,38 @@ pub fn derive_selection_state(input: proc_macro::TokenStream) -> proc_macro::Tok
                     ::serenity::all::ComponentInteractionDataKind::StringSelect { ref values } => {
                         match interaction.data.custom_id.as_str() {
                             #(#other_fields)*
-                            _ => return Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown custom_id!")))
+                            _ => return Err(crate::error::BotError::from(::serenity::Error::Other("Unknown custom_id!")))
                         }
                     }
 
                     ::serenity::all::ComponentInteractionDataKind::UserSelect { ref values } => {
                         match interaction.data.custom_id.as_str() {
                             #(#user_id_fields)*
-                            _ => return Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown custom_id!")))
+                            _ => return Err(crate::error::BotError::from(::serenity::Error::Other("Unknown custom_id!")))
                         }
                     }
 
                     ::serenity::all::ComponentInteractionDataKind::RoleSelect { ref values } => {
                         match interaction.data.custom_id.as_str() {
                             #(#role_id_fields)*
-                            _ => return Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown custom_id!")))
+                            _ => return Err(crate::error::BotError::from(::serenity::Error::Other("Unknown custom_id!")))
                         }
                     }
 
                     ::serenity::all::ComponentInteractionDataKind::ChannelSelect { ref values } => {
                         match interaction.data.custom_id.as_str() {
                             #(#channel_id_fields)*
-                            _ => return Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown custom_id!")))
+                            _ => return Err(crate::error::BotError::from(::serenity::Error::Other("Unknown custom_id!")))
                         }
                     }
 
                     ::serenity::all::ComponentInteractionDataKind::MentionableSelect { ref values } => {
                         match interaction.data.custom_id.as_str() {
                             #(#generic_id_fields)*
-                            _ => return Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown custom_id!")))
+                            _ => return Err(crate::error::BotError::from(::serenity::Error::Other("Unknown custom_id!")))
                         }
                     }
-                    _ => return Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown interaction type!")))
+                    _ => return Err(crate::error::BotError::from(::serenity::Error::Other("Unknown interaction type!")))
                 }
                 Ok(())
             }
@@ -183,7 +183,7 @@ pub fn derive_selection(input: proc_macro::TokenStream) -> proc_macro::TokenStre
             pub fn from_str(s: &::std::primitive::str) -> crate::aliases::TypedResult<Self> {
                 match s {
                     #(#from_match,)*
-                    _ => Err(crate::error::BotError::Serenity(::serenity::Error::Other("Unknown custom_id!")))
+                    _ => Err(crate::error::BotError::from(::serenity::Error::Other("Unknown custom_id!")))
                 }
             }
         }
@@ -274,6 +274,16 @@ pub fn interactive_msg(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
         }
     });
 
+    let capabilities = rows.iter().flat_map(|row| {
+        match &row.component {
+            RowComponent::Buttons(b) => b.iter().filter_map(|button| {
+                let id = button.id.value();
+                button.capability.as_ref().map(|capability| quote! { #id => crate::permissions::Capability::#capability, })
+            }).collect::<Vec<_>>(),
+            _ => vec![],
+        }
+    });
+
     let handler_name = struct_tag.handler_name;
     let merged_handler_trait_ident = Ident::new(&format!("{}Trait", handler_name.to_string()), handler_name.span());
     let ephemeral = struct_tag.ephemeral;
@@ -314,6 +324,13 @@ pub fn interactive_msg(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 ::std::vec![#(#embeds_event::from_event(ctx).await)*]
             }
 
+            fn capability(custom_id: &::std::primitive::str) -> crate::permissions::Capability {
+                match custom_id {
+                    #(#capabilities)*
+                    _ => crate::permissions::Capability::Everyone
+                }
+            }
+
             async fn handle_event(ctx: &mut crate::components::EventCtx) -> crate::aliases::Result {
                 match ctx.interaction.data.custom_id.as_str() {
                     #(#handle_func)*
@@ -15,13 +15,15 @@ macro_rules! optional_attr {
 pub struct CommandTag {
     pub struct_name: Ident,
     pub description: Option<LitStr>,
+    pub subcommand: Option<LitStr>,
     pub options: Vec<CommandOptionTag>,
 }
 
 impl Parse for CommandTag {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         input.parse::<Token![<]>()?;
-        let tag = input.parse::<Tag>()?;
+        let mut tag = input.parse::<Tag>()?;
+        let subcommand = tag.attr("subcommand")?;
 
         let mut desc: Option<LitStr> = None;
         let mut options: Vec<CommandOptionTag> = vec![];
@@ -57,6 +59,7 @@ impl Parse for CommandTag {
         Ok(Self {
             struct_name: tag.name,
             description: desc,
+            subcommand,
             options,
         })
     }
@@ -81,6 +84,7 @@ impl ToTokens for CommandTag {
                 "Number" => quote! { ::std::primitive::f64 },
                 "Integer" => quote! { ::std::primitive::i64 },
                 "Boolean" => quote! { ::std::primitive::bool },
+                "Attachment" => quote! { ::serenity::all::AttachmentId },
                 _ => quote! { &'o ::std::primitive::str },
             };
 
@@ -95,6 +99,11 @@ impl ToTokens for CommandTag {
             quote! { pub #i: #ttype, }
         });
 
+        let choices = options.clone().filter_map(|option| {
+            let choices = option.choices.as_ref()?;
+            Some(quote! { #choices: &[::std::string::String], })
+        });
+
         let option_match = options.clone().map(|option| {
             let name = &option.name;
             let i = Ident::new(&option.name.value().to_lowercase(), option.span());
@@ -105,6 +114,7 @@ impl ToTokens for CommandTag {
                 "Number" => quote! { .as_f64() },
                 "Integer" => quote! { .as_i64() },
                 "Boolean" => quote! { .as_bool() },
+                "Attachment" => quote! { .as_attachment_id() },
                 _ => quote! { .as_str() },
             };
 
@@ -119,16 +129,50 @@ impl ToTokens for CommandTag {
             quote! { #name => s.#i = option.value #eq, }
         });
 
+        // with a subcommand the options live one level deeper, under it
+        let (new, data_options) = match &self.subcommand {
+            None => (
+                quote! { command #description #(.add_option(#options))* },
+                quote! { ctx.interaction.data.options.iter() },
+            ),
+            Some(subcommand) => {
+                let desc = match &self.description {
+                    Some(desc) => quote! { #desc },
+                    None => quote! { "" },
+                };
+                (
+                    quote! {
+                        command.add_option(
+                            ::serenity::all::CreateCommandOption::new(
+                                ::serenity::all::CommandOptionType::SubCommand,
+                                #subcommand,
+                                #desc,
+                            )
+                            #(.add_sub_option(#options))*
+                        )
+                    },
+                    quote! {
+                        ctx.interaction.data.options.iter()
+                            .filter(|option| option.name == #subcommand)
+                            .flat_map(|option| match &option.value {
+                                ::serenity::all::CommandDataOptionValue::SubCommand(options) => options.as_slice(),
+                                _ => &[],
+                            })
+                    },
+                )
+            }
+        };
+
         tokens.extend(quote! {
             struct #struct_name;
             impl #struct_name {
-                fn new(command: ::serenity::all::CreateCommand) -> ::serenity::all::CreateCommand {
-                    command #description #(.add_option(#options))*
+                fn new(command: ::serenity::all::CreateCommand, #(#choices)*) -> ::serenity::all::CreateCommand {
+                    #new
                 }
 
                 fn options<'o>(ctx: &crate::components::CommandCtx<'o>) -> #option_struct_name<'o> {
                     let mut s = #option_struct_name::default();
-                    for option in &ctx.interaction.data.options {
+                    for option in #data_options {
                         match option.name.as_str() {
                             #(#option_match)*
                             _ => unreachable!()
@@ -142,6 +186,8 @@ impl ToTokens for CommandTag {
             #[derive(::std::default::Default)]
             struct #option_struct_name<'o> {
                 #(#option_fields)*
+                // not every command has an option borrowing from the interaction
+                _interaction: ::std::marker::PhantomData<&'o ()>,
             }
         });
     }
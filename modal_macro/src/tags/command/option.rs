@@ -19,6 +19,8 @@ pub struct CommandOptionTag {
     pub required: Option<LitBool>,
     min_len: Option<LitInt>,
     max_len: Option<LitInt>,
+    /// Parameter of the command's `new` holding the choices, for ones only known at runtime
+    pub choices: Option<Ident>,
 }
 
 impl Parse for CommandOptionTag {
@@ -57,6 +59,7 @@ impl Parse for CommandOptionTag {
 
         let min_len = tag.attr("min_len")?;
         let max_len = tag.attr("max_len")?;
+        let choices = tag.attr("choices")?;
 
         if tag.name != closing.name {
             return Err(syn::Error::new(closing.name.span(), "unclosed tag"));
@@ -81,6 +84,7 @@ impl Parse for CommandOptionTag {
             required,
             min_len,
             max_len,
+            choices,
         })
     }
 }
@@ -95,8 +99,19 @@ impl ToTokens for CommandOptionTag {
         optional_attr!(self, min_len, .min_length(#min_len));
         optional_attr!(self, max_len, .min_length(#max_len));
 
-        tokens.extend(quote! {
+        let option = quote! {
             ::serenity::all::CreateCommandOption::new(::serenity::all::CommandOptionType::#ttype, #name, #desc)#required #min_len #max_len
+        };
+
+        tokens.extend(match &self.choices {
+            None => option,
+            Some(choices) => quote! {{
+                let mut option = #option;
+                for choice in #choices {
+                    option = option.add_string_choice(choice, choice);
+                }
+                option
+            }},
         });
     }
 }
@@ -18,7 +18,7 @@ impl Parse for TypeTag {
 
         match ttype.to_string().as_str() {
             "User" | "String" | "Number" | "Boolean" | "Integer" | "SubCommand" | "Channel"
-            | "Role" | "Mentionable" => (),
+            | "Role" | "Mentionable" | "Attachment" => (),
             _ => return Err(syn::Error::new(ttype.span(), "invalid type")),
         };
 
@@ -20,6 +20,8 @@ pub struct ButtonTag {
     pub link: Option<LitStr>,
     pub style: Option<Ident>,
     pub disabled: Option<LitBool>,
+    /// What the user needs to be allowed to press it, see `crate::permissions::Capability`
+    pub capability: Option<Ident>,
 }
 
 impl Parse for ButtonTag {
@@ -42,6 +44,7 @@ impl Parse for ButtonTag {
 
         let link = tag.attr::<LitStr>("link")?;
         let disabled = tag.attr::<LitBool>("disabled")?;
+        let capability = tag.attr::<Ident>("capability")?;
         let s = tag.attr::<LitStr>("style")?;
         let style = if let Some(attr) = s {
             match attr.value().as_str() {
@@ -66,6 +69,7 @@ impl Parse for ButtonTag {
             disabled,
             link,
             style,
+            capability,
         });
     }
 }
@@ -4,7 +4,10 @@ use chrono::NaiveDateTime;
 use chrono_tz::Tz;
 use ical::parser::ical::component::IcalCalendar;
 
-use crate::{calendar::Event, map_properties};
+use crate::{
+    calendar::{zone::Zones, Event},
+    map_properties,
+};
 
 #[derive(Debug, Clone)]
 pub struct Calendar {
@@ -17,15 +20,8 @@ pub struct Calendar {
 impl TryFrom<IcalCalendar> for Calendar {
     type Error = chrono::ParseError;
     fn try_from(value: IcalCalendar) -> Result<Self, Self::Error> {
-        let events = value
-            .events
-            .iter()
-            .cloned()
-            .map(|ical_event| Event::try_from(ical_event))
-            .collect::<Result<Vec<Event>, chrono::ParseError>>()?;
-
         let mut s = Self {
-            events,
+            events: vec![],
             name: String::default(),
             description: String::default(),
             timezone: Tz::default(),
@@ -37,6 +33,13 @@ impl TryFrom<IcalCalendar> for Calendar {
             "X-WR-TIMEZONE" => s.timezone; with tz { tz.parse().map_err(|_| NaiveDateTime::from_str("invalid timezone").err().unwrap())? },
         );
 
+        let zones = Zones::new(s.timezone, &value.timezones);
+        s.events = value
+            .events
+            .into_iter()
+            .map(|event| Event::parse(event, &zones))
+            .collect::<Result<Vec<Event>, chrono::ParseError>>()?;
+
         Ok(s)
     }
 }
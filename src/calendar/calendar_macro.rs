@@ -1,20 +1,31 @@
 #[macro_export]
 macro_rules! map_properties {
-    ($from:ident.$from_field:ident, $($prop: expr => $struct:ident.$field:ident $(;with $var:ident { $($closure:tt)* })?,)+) => {
+    ($from:ident.$from_field:ident, $($prop: expr => $struct:ident.$field:ident $(;with $var:ident $(, $params:ident)? { $($closure:tt)* })?,)+) => {
         for prop in $from.$from_field {
             match prop.name.as_str() {
-                $($prop => if let Some(p) = prop.value { map_properties!(@handle p $struct $field $(,$var, $($closure)* )?); })+
+                $($prop => if let Some(p) = prop.value {
+                    let params = prop.params;
+                    map_properties!(@handle p params; $struct $field $(, $var $(, $params)? { $($closure)* })?);
+                })+
                 _ => (),
             }
         }
     };
 
-    (@handle $prop:ident $struct:ident $field:ident) => {
-        $struct.$field = $prop
-    };
+    (@handle $prop:ident $prop_params:ident; $struct:ident $field:ident) => {{
+            let _ = $prop_params;
+            $struct.$field = $prop;
+    }};
+
+    (@handle $prop:ident $prop_params:ident; $struct:ident $field:ident, $var:ident { $($closure:tt)* }) => {{
+            let _ = $prop_params;
+            let $var = $prop;
+            $struct.$field = $($closure)*;
+    }};
 
-    (@handle $prop:ident $struct:ident $field:ident, $var:ident, $($closure:tt)*) => {{
+    (@handle $prop:ident $prop_params:ident; $struct:ident $field:ident, $var:ident, $params:ident { $($closure:tt)* }) => {{
             let $var = $prop;
+            let $params = $prop_params;
             $struct.$field = $($closure)*;
     }};
 }
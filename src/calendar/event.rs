@@ -1,13 +1,51 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
 use ical::parser::ical::component::IcalEvent;
 
-use crate::{calendar::CalendarParser, map_properties};
+use crate::{
+    calendar::{
+        recurrence::{Recurrence, Rule},
+        zone::{Params, Zone, Zones},
+        CalendarParser,
+    },
+    map_properties,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventStatus {
+    Tentative,
+    Confirmed,
+    Cancelled,
+}
+
+impl EventStatus {
+    fn parse(status: &str) -> Option<Self> {
+        match status.trim().to_uppercase().as_str() {
+            "TENTATIVE" => Some(Self::Tentative),
+            "CONFIRMED" => Some(Self::Confirmed),
+            "CANCELLED" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Event {
     pub uid: String,
     pub summary: String,
     pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    /// Starts at midnight and has no time of day
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub status: Option<EventStatus>,
+    pub categories: Vec<String>,
+    /// How a recurring event repeats, None once expanded into its occurrences
+    pub recurrence: Option<Recurrence>,
+    /// Which occurrence of a recurring event this is, by when it originally started
+    pub recurrence_id: Option<DateTime<Utc>>,
 }
 
 impl PartialEq for Event {
@@ -20,7 +58,7 @@ impl Eq for Event {}
 
 impl PartialOrd for Event {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.start.partial_cmp(&other.start)
+        Some(self.cmp(other))
     }
 }
 
@@ -30,21 +68,104 @@ impl Ord for Event {
     }
 }
 
-impl TryFrom<IcalEvent> for Event {
-    type Error = chrono::ParseError;
-    fn try_from(value: IcalEvent) -> Result<Self, Self::Error> {
-        let mut s = Self {
-            uid: String::default(),
-            summary: String::default(),
-            start: DateTime::default(),
-        };
+#[derive(Default)]
+struct Times {
+    start: Option<(String, Params)>,
+    end: Option<(DateTime<Utc>, bool)>,
+    duration: Option<Duration>,
+    rule: Option<String>,
+    exdates: Vec<DateTime<Utc>>,
+}
+
+impl Event {
+    /// Tells the occurrences of a recurring event apart, they all share its uid
+    pub fn sync_uid(&self) -> String {
+        match self.recurrence_id {
+            Some(occurrence) => format!("{}/{}", self.uid, occurrence.timestamp()),
+            None => self.uid.clone(),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status == Some(EventStatus::Cancelled)
+    }
+
+    /// The last moment an RRULE's UNTIL allows. A date keeps the whole day,
+    /// and a time without a "Z" is in DTSTART's zone
+    fn parse_until(until: &str, zone: Zone) -> Option<DateTime<Utc>> {
+        let until = until.trim();
+        if until.len() == 8 {
+            let next_day = NaiveDate::parse_from_str(until, "%Y%m%d").ok()?.succ_opt()?;
+            Some(zone.to_utc(next_day.and_time(NaiveTime::MIN)) - Duration::seconds(1))
+        } else if until.ends_with('Z') {
+            let naive = NaiveDateTime::parse_from_str(until, "%Y%m%dT%H%M%SZ").ok()?;
+            Some(naive.and_utc())
+        } else {
+            let naive = NaiveDateTime::parse_from_str(until, "%Y%m%dT%H%M%S").ok()?;
+            Some(zone.to_utc(naive))
+        }
+    }
+
+    /// Times without a TZID are in the calendar's timezone, see [`Zones`]
+    pub fn parse(value: IcalEvent, zones: &Zones) -> Result<Self, chrono::ParseError> {
+        let mut s = Self::default();
+        let mut times = Times::default();
 
         map_properties!(value.properties,
-            "SUMMARY" => s.summary,
-            "DTSTART" => s.start; with date { CalendarParser::parse_date(&date)? },
             "UID" => s.uid,
+            "SUMMARY" => s.summary; with summary { CalendarParser::unescape(&summary) },
+            "DTSTART" => times.start; with date, params { Some((date, params)) },
+            "DTEND" => times.end; with date, params { Some(zones.parse(&date, &params)?) },
+            "DURATION" => times.duration; with duration { CalendarParser::parse_duration(&duration) },
+            "LOCATION" => s.location; with location { Some(CalendarParser::unescape(&location)) },
+            "DESCRIPTION" => s.description; with description { Some(CalendarParser::unescape(&description)) },
+            "URL" => s.url; with url { Some(url.trim().to_owned()) },
+            "STATUS" => s.status; with status { EventStatus::parse(&status) },
+            "RRULE" => times.rule; with rule { Some(rule) },
+            "EXDATE" => times.exdates; with dates, params {
+                [
+                    std::mem::take(&mut times.exdates),
+                    dates
+                        .split(',')
+                        .map(|date| zones.parse(date, &params).map(|(date, _)| date))
+                        .collect::<Result<Vec<_>, _>>()?,
+                ]
+                .concat()
+            },
+            "RECURRENCE-ID" => s.recurrence_id; with date, params { Some(zones.parse(&date, &params)?.0) },
+            "CATEGORIES" => s.categories; with categories {
+                [std::mem::take(&mut s.categories), CalendarParser::split_list(&categories)].concat()
+            },
         );
 
+        if let Some((date, params)) = &times.start {
+            (s.start, s.all_day) = zones.parse(date, params)?;
+            let zone = match date.trim().ends_with('Z') {
+                true => Zone::Named(Tz::UTC),
+                false => zones.zone(params),
+            };
+            s.recurrence = times
+                .rule
+                .and_then(|rule| {
+                    Rule::parse(&rule, |until| Self::parse_until(until, zone))
+                })
+                .map(|rule| Recurrence {
+                    rule,
+                    zone,
+                    exdates: times.exdates,
+                });
+        }
+        s.end = match (times.end, times.duration) {
+            (Some((end, _)), _) => Some(end),
+            (None, Some(duration)) => s.start.checked_add_signed(duration),
+            // a whole day event without an end takes that day
+            (None, None) if s.all_day => Some(s.start + Duration::days(1)),
+            (None, None) => None,
+        };
+        s.location = s.location.filter(|l| !l.trim().is_empty());
+        s.description = s.description.filter(|d| !d.trim().is_empty());
+        s.url = s.url.filter(|u| !u.is_empty());
+
         Ok(s)
     }
 }
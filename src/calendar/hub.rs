@@ -1,77 +1,141 @@
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 
 use chrono::Utc;
-use tokio::{sync::Mutex, sync::RwLock, time::Instant};
+use tokio::{sync::Mutex, time::Instant};
 
 use crate::{
-    calendar::{Calendar, CalendarParser},
+    calendar::{recurrence::expand, Calendar, CalendarParser, CalendarSource, EventSelection},
     log, log_error,
 };
 
+#[derive(Debug, Default, Clone)]
+struct Loaded {
+    /// Every upcoming event, for previewing other selections
+    all: Option<Arc<Calendar>>,
+    /// The events the source selects, as they're shown
+    calendar: Option<Arc<Calendar>>,
+    refreshed: Option<Instant>,
+    /// The last load failed, the calendars above are from an earlier one if any
+    failed: bool,
+}
+
 #[derive(Debug)]
 pub struct CalendarHub {
-    url: String,
-    calendars: RwLock<Vec<Arc<Calendar>>>,
-    last_refresh: Mutex<Instant>,
+    sources: Vec<(CalendarSource, Mutex<Loaded>)>,
 }
 
 impl CalendarHub {
-    pub async fn new(url: impl Into<String>) -> Self {
+    pub async fn new(sources: Vec<CalendarSource>) -> Self {
         let s = Self {
-            url: url.into(),
-            calendars: RwLock::new(vec![]),
-            last_refresh: Mutex::new(Instant::now()),
+            sources: sources
+                .into_iter()
+                .map(|source| (source, Mutex::new(Loaded::default())))
+                .collect(),
         };
 
-        s.update().await;
+        for (source, loaded) in &s.sources {
+            Self::update(source, &mut *loaded.lock().await).await;
+        }
 
         s
     }
 
-    pub async fn update(&self) {
-        *self.calendars.write().await = match CalendarParser::fetch_and_parse(&self.url).await {
+    /// Every calendar in the source merged into one, named after the source
+    async fn update(source: &CalendarSource, loaded: &mut Loaded) {
+        // a failed load waits out the interval too, instead of being retried by every caller
+        loaded.refreshed = Some(Instant::now());
+        let calendars = match CalendarParser::load(source).await {
             Err(e) => {
-                log_error!("Failed to update calendar! {e}");
+                log_error!("Failed to update calendar {}! {e}", source.name);
+                loaded.failed = true;
                 return;
             }
-            Ok(v) => match v {
-                Ok(r) => match r {
-                    Ok(mut c) => {
-                        let now = Utc::now();
-                        c.iter_mut().for_each(|cal| {
-                            cal.events.retain(|event| {
-                                event.summary.contains("--BOT--") && event.start > now
-                            });
-                            cal.events.sort_unstable()
-                        });
-                        c.into_iter().map(Arc::new).collect()
-                    }
-                    Err(e) => {
-                        log_error!("Failed to update calendar! {e}");
-                        return;
-                    }
-                },
-                Err(e) => {
-                    log_error!("Failed to update calendar! {e}");
-                    return;
-                }
-            },
+            Ok(c) => c,
         };
+        loaded.failed = false;
 
-        *self.last_refresh.lock().await = Instant::now();
+        let now = Utc::now();
+        let mut all = Calendar {
+            name: source.name.clone(),
+            description: String::new(),
+            events: vec![],
+            timezone: Default::default(),
+        };
+        for cal in calendars {
+            if all.description.is_empty() {
+                all.description = cal.description;
+                all.timezone = cal.timezone;
+            }
+            all.events.extend(
+                expand(cal.events, now + source.lookahead())
+                    .into_iter()
+                    .filter(|event| event.start > now),
+            );
+        }
+        all.events.sort_unstable();
+
+        let calendar = Calendar {
+            events: all
+                .events
+                .iter()
+                .filter(|event| source.select.matches(event))
+                .map(|event| source.select.strip(event.clone()))
+                .collect(),
+            ..all.clone()
+        };
+
+        loaded.all = Some(Arc::new(all));
+        loaded.calendar = Some(Arc::new(calendar));
     }
 
+    async fn get(source: &CalendarSource, loaded: &Mutex<Loaded>) -> Loaded {
+        let mut loaded = loaded.lock().await;
+        if loaded
+            .refreshed
+            .is_none_or(|r| r.elapsed() > source.refresh_interval())
+        {
+            log!("Refreshing calendar {}!", source.name);
+            Self::update(source, &mut loaded).await;
+        }
+
+        loaded.clone()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.sources.iter().map(|(s, _)| s.name.clone()).collect()
+    }
+
+    /// None when there's no such calendar or it couldn't be loaded yet
     pub async fn get_calendar(&self, name: &str) -> Option<Arc<Calendar>> {
-        if self.last_refresh.lock().await.elapsed() > Duration::from_secs(3600) {
-            log!("Refreshing calendar cache!");
-            self.update().await;
+        let (source, loaded) = self.sources.iter().find(|(s, _)| s.name == name)?;
+        Self::get(source, loaded).await.calendar
+    }
+
+    /// Every upcoming event of the calendar, whether its selection picks it or not,
+    /// with the selection it's configured with
+    pub async fn get_unselected(&self, name: &str) -> Option<(Arc<Calendar>, EventSelection)> {
+        let (source, loaded) = self.sources.iter().find(|(s, _)| s.name == name)?;
+        let all = Self::get(source, loaded).await.all?;
+        Some((all, source.select.clone()))
+    }
+
+    /// Every source by name, with its calendar only when the last load of it worked
+    pub async fn get_loaded(&self) -> Vec<(String, Option<Arc<Calendar>>)> {
+        let mut calendars = vec![];
+        for (source, loaded) in &self.sources {
+            let loaded = Self::get(source, loaded).await;
+            let calendar = loaded.calendar.filter(|_| !loaded.failed);
+            calendars.push((source.name.clone(), calendar));
         }
+        calendars
+    }
 
-        self.calendars
-            .read()
-            .await
-            .iter()
-            .find(|c| c.name == name)
-            .cloned()
+    /// Every calendar that could be loaded
+    pub async fn get_calendars(&self) -> Vec<Arc<Calendar>> {
+        let mut calendars = vec![];
+        for (source, loaded) in &self.sources {
+            calendars.extend(Self::get(source, loaded).await.calendar);
+        }
+        calendars
     }
 }
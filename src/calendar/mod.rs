@@ -1,10 +1,17 @@
+#[allow(clippy::module_inception)]
 pub mod calendar;
 mod calendar_macro;
 pub mod event;
 pub mod hub;
 pub mod parser;
+pub mod recurrence;
+pub mod selection;
+pub mod source;
+pub mod zone;
 
 pub use calendar::Calendar;
-pub use event::Event;
+pub use event::{Event, EventStatus};
 pub use hub::CalendarHub;
 pub use parser::CalendarParser;
+pub use selection::EventSelection;
+pub use source::CalendarSource;
@@ -1,46 +1,133 @@
-use std::io::Cursor;
+use std::{fmt::Display, io::Cursor};
 
-use chrono::{DateTime, NaiveDateTime, ParseError, TimeZone, Utc};
+use chrono::{Duration, ParseError};
 use ical::{parser::ParserError, IcalParser};
 
-use crate::calendar::Calendar;
+use crate::calendar::{Calendar, CalendarSource};
+
+/// A calendar server that stops answering would otherwise hold up everyone waiting on the hub
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum CalendarError {
+    Fetch(reqwest::Error),
+    Read(std::io::Error),
+    Ical(ParserError),
+    Date(ParseError),
+}
+
+impl Display for CalendarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fetch(e) => write!(f, "fetching: {e}"),
+            Self::Read(e) => write!(f, "reading: {e}"),
+            Self::Ical(e) => write!(f, "ical: {e}"),
+            Self::Date(e) => write!(f, "date: {e}"),
+        }
+    }
+}
 
 pub struct CalendarParser;
 
 impl CalendarParser {
     async fn fetch(url: &str) -> Result<String, reqwest::Error> {
-        reqwest::get(url).await?.text().await
+        reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()?
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await
     }
 
     fn parse(data: String) -> Result<Result<Vec<Calendar>, chrono::ParseError>, ParserError> {
         let parser = IcalParser::new(Cursor::new(data));
         parser
-            .map(|maybe_ical| maybe_ical.map(|ical| Calendar::try_from(ical)))
+            .map(|maybe_ical| maybe_ical.map(Calendar::try_from))
             .collect::<Result<Result<Vec<Calendar>, chrono::ParseError>, ParserError>>()
     }
 
-    pub async fn fetch_and_parse(
-        url: &str,
-    ) -> Result<Result<Result<Vec<Calendar>, chrono::ParseError>, ParserError>, reqwest::Error>
-    {
-        Ok(Self::parse(Self::fetch(url).await?))
-    }
-
-    pub fn parse_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
-        let date = date.trim();
-        if date.ends_with('Z') {
-            let naive = NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%SZ")?;
-            Ok(Utc.from_utc_datetime(&naive))
-        } else if date.len() == 8 {
-            let naive =
-                match chrono::NaiveDate::parse_from_str(date, "%Y%m%d")?.and_hms_opt(0, 0, 0) {
-                    Some(hms) => hms,
-                    None => NaiveDateTime::default(),
-                };
-            Ok(Utc.from_utc_datetime(&naive))
+    pub async fn load(source: &CalendarSource) -> Result<Vec<Calendar>, CalendarError> {
+        let data = if source.is_url() {
+            Self::fetch(&source.source)
+                .await
+                .map_err(CalendarError::Fetch)?
         } else {
-            let naive = chrono::NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%S")?;
-            Ok(Utc.from_utc_datetime(&naive))
+            tokio::fs::read_to_string(&source.source)
+                .await
+                .map_err(CalendarError::Read)?
+        };
+
+        Self::parse(data)
+            .map_err(CalendarError::Ical)?
+            .map_err(CalendarError::Date)
+    }
+
+    /// "PT1H30M", "P1D" or "P2W", None for anything else
+    pub fn parse_duration(duration: &str) -> Option<Duration> {
+        let duration = duration.trim();
+        let (sign, duration) = match duration.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, duration.strip_prefix('+').unwrap_or(duration)),
+        };
+
+        let mut total = Duration::zero();
+        let mut number = String::new();
+        let mut in_time = false;
+        for c in duration.strip_prefix('P')?.chars() {
+            match c {
+                'T' => in_time = true,
+                '0'..='9' => number.push(c),
+                unit => {
+                    let n = std::mem::take(&mut number).parse().ok()?;
+                    // a huge number is as broken as an unknown unit
+                    let part = match (unit, in_time) {
+                        ('W', false) => Duration::try_weeks(n),
+                        ('D', false) => Duration::try_days(n),
+                        ('H', true) => Duration::try_hours(n),
+                        ('M', true) => Duration::try_minutes(n),
+                        ('S', true) => Duration::try_seconds(n),
+                        _ => None,
+                    }?;
+                    total = total.checked_add(&part)?;
+                }
+            }
+        }
+
+        number.is_empty().then_some(total * sign)
+    }
+
+    /// Undoes the escaping of TEXT values, like "\\n" for a new line
+    pub fn unescape(text: &str) -> String {
+        Self::split_unescaped(text, None).concat()
+    }
+
+    /// The items of a comma separated TEXT list like CATEGORIES
+    pub fn split_list(text: &str) -> Vec<String> {
+        Self::split_unescaped(text, Some(','))
+            .into_iter()
+            .map(|item| item.trim().to_owned())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    fn split_unescaped(text: &str, separator: Option<char>) -> Vec<String> {
+        let mut items = vec![String::new()];
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            let item = items.last_mut().expect("there's always an item");
+            match c {
+                '\\' => match chars.next() {
+                    Some('n' | 'N') => item.push('\n'),
+                    Some(escaped) => item.push(escaped),
+                    None => item.push('\\'),
+                },
+                c if Some(c) == separator => items.push(String::new()),
+                c => item.push(c),
+            }
         }
+        items
     }
 }
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc, Weekday};
+
+use crate::calendar::{zone::Zone, Event};
+
+/// Stops rules that never produce an occurrence in the window from spinning forever
+const MAX_PERIODS: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The parts of an RRULE we understand: FREQ, INTERVAL, BYDAY, COUNT and UNTIL
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    /// A weekday, and for monthly and yearly rules which one of the period, e.g. -1 for the last
+    pub by_day: Vec<(Option<i32>, Weekday)>,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Rule {
+    /// `until` turns the UNTIL value into a time, since it can be a date, in UTC or floating.
+    /// None for rules we can't expand, which then only happen once
+    pub fn parse(rule: &str, until: impl Fn(&str) -> Option<DateTime<Utc>>) -> Option<Self> {
+        let mut s = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: vec![],
+            count: None,
+            until: None,
+        };
+
+        let mut frequency = None;
+        for part in rule.trim().split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=')?;
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => s.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => s.count = Some(value.parse().ok()?),
+                "UNTIL" => s.until = Some(until(value)?),
+                "BYDAY" => {
+                    s.by_day = value
+                        .split(',')
+                        .map(parse_day)
+                        .collect::<Option<Vec<_>>>()?
+                }
+                // WKST only matters for weekly rules with an interval and BYDAY, Monday is the default
+                "WKST" => (),
+                // anything else narrows the rule down in ways we'd get wrong
+                _ => return None,
+            }
+        }
+
+        s.frequency = frequency?;
+        Some(s)
+    }
+
+    /// The days of the period starting on `first` that the rule picks
+    fn days(&self, first: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        let last = match self.frequency {
+            Frequency::Daily => first,
+            Frequency::Weekly => first + Duration::days(6),
+            Frequency::Monthly => first + Months::new(1) - Duration::days(1),
+            Frequency::Yearly => first + Months::new(12) - Duration::days(1),
+        };
+        let period = first.iter_days().take_while(|d| *d <= last);
+
+        if self.by_day.is_empty() {
+            return match self.frequency {
+                Frequency::Daily => vec![first],
+                Frequency::Weekly => period.filter(|d| d.weekday() == start.weekday()).collect(),
+                Frequency::Monthly => first.with_day(start.day()).into_iter().collect(),
+                Frequency::Yearly => {
+                    NaiveDate::from_ymd_opt(first.year(), start.month(), start.day())
+                        .into_iter()
+                        .collect()
+                }
+            };
+        }
+
+        let days = period.collect::<Vec<_>>();
+        days.iter()
+            .copied()
+            .filter(|day| {
+                self.by_day.iter().any(|(nth, weekday)| {
+                    if day.weekday() != *weekday {
+                        return false;
+                    }
+                    let Some(nth) = nth.filter(|_| {
+                        matches!(self.frequency, Frequency::Monthly | Frequency::Yearly)
+                    }) else {
+                        return true;
+                    };
+                    let same = days.iter().filter(|d| d.weekday() == *weekday);
+                    if nth > 0 {
+                        same.clone().nth(nth as usize - 1) == Some(day)
+                    } else {
+                        same.rev().nth(nth.unsigned_abs() as usize - 1) == Some(day)
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The first day of the `n`th period after the one `start` is in
+    fn period(&self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        let steps = n.checked_mul(self.interval)?;
+        match self.frequency {
+            Frequency::Daily => start.checked_add_signed(Duration::days(steps.into())),
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday().into());
+                monday.checked_add_signed(Duration::weeks(steps.into()))
+            }
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(steps)),
+            Frequency::Yearly => start
+                .with_day(1)?
+                .with_month(1)?
+                .checked_add_months(Months::new(steps.checked_mul(12)?)),
+        }
+    }
+}
+
+/// "MO", "2TU" or "-1FR"
+fn parse_day(day: &str) -> Option<(Option<i32>, Weekday)> {
+    let day = day.trim();
+    let (nth, weekday) = day.split_at(day.len().checked_sub(2)?);
+    let weekday = match weekday.to_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    let nth = match nth {
+        "" => None,
+        nth => Some(nth.parse().ok().filter(|n: &i32| *n != 0)?),
+    };
+    Some((nth, weekday))
+}
+
+/// How a recurring event repeats
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    pub rule: Rule,
+    /// Occurrences happen at the same local time in this zone, across daylight saving changes
+    pub zone: Zone,
+    /// Occurrences that were deleted
+    pub exdates: Vec<DateTime<Utc>>,
+}
+
+impl Recurrence {
+    /// When the event starting at `start` happens up to `to`, EXDATEs left out
+    pub fn occurrences(&self, start: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let local = self.zone.to_local(start);
+        let mut occurrences = vec![];
+        let mut counted = 0;
+
+        'periods: for n in 0..MAX_PERIODS {
+            let Some(first) = self.rule.period(local.date(), n) else {
+                break;
+            };
+            if self.zone.to_utc(first.and_time(local.time())) > to + Duration::days(1) {
+                break;
+            }
+            for day in self.rule.days(first, local.date()) {
+                let when = self.zone.to_utc(day.and_time(local.time()));
+                if when < start {
+                    continue;
+                }
+                if when > to
+                    || self.rule.until.is_some_and(|until| when > until)
+                    || self.rule.count.is_some_and(|count| counted >= count)
+                {
+                    break 'periods;
+                }
+
+                counted += 1;
+                if !self.exdates.contains(&when) {
+                    occurrences.push(when);
+                }
+            }
+        }
+
+        occurrences
+    }
+}
+
+/// Every occurrence of the recurring events up to `to` as an event of its own, with modified
+/// ones (those with a RECURRENCE-ID) in place of the occurrences they change
+pub fn expand(events: Vec<Event>, to: DateTime<Utc>) -> Vec<Event> {
+    let (modified, events): (Vec<_>, Vec<_>) = events
+        .into_iter()
+        .partition(|e| e.recurrence_id.is_some() && e.recurrence.is_none());
+    let modified = modified
+        .into_iter()
+        .map(|e| ((e.uid.clone(), e.recurrence_id), e))
+        .collect::<HashMap<_, _>>();
+
+    let mut expanded = vec![];
+    for event in events {
+        let Some(recurrence) = event.recurrence.clone() else {
+            expanded.push(event);
+            continue;
+        };
+
+        let length = event.end.map(|end| end - event.start);
+        for start in recurrence.occurrences(event.start, to) {
+            if modified.contains_key(&(event.uid.clone(), Some(start))) {
+                continue;
+            }
+            expanded.push(Event {
+                start,
+                end: length.and_then(|l| start.checked_add_signed(l)),
+                recurrence: None,
+                recurrence_id: Some(start),
+                ..event.clone()
+            });
+        }
+    }
+
+    // a modified occurrence can be moved into the window from outside, so they're all kept
+    expanded.extend(modified.into_values());
+    expanded
+}
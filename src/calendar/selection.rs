@@ -0,0 +1,103 @@
+use std::{fmt::Display, str::FromStr};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::calendar::Event;
+
+/// Which of a calendar's events the bot shows, written in the config as
+/// "all", "tag:--BOT--", "categories:Club, Meetings" or "regex:^\[ALGO\]".
+/// Anything else is a tag, so the old `"filter": "--BOT--"` keeps working.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum EventSelection {
+    #[default]
+    All,
+    /// Events with this in their summary, it's taken out when they're shown
+    Tag(String),
+    /// Events in any of these CATEGORIES, in any case
+    Categories(Vec<String>),
+    /// Events whose summary matches
+    Regex(Regex),
+}
+
+impl FromStr for EventSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() || s.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+
+        let selection = match s.split_once(':') {
+            Some(("tag", tag)) => Self::Tag(tag.trim().to_owned()),
+            Some(("categories", categories)) => Self::Categories(
+                categories
+                    .split(',')
+                    .map(|c| c.trim().to_owned())
+                    .filter(|c| !c.is_empty())
+                    .collect(),
+            ),
+            Some(("regex", pattern)) => {
+                Self::Regex(Regex::new(pattern).map_err(|e| format!("invalid regex: {e}"))?)
+            }
+            _ => Self::Tag(s.to_owned()),
+        };
+
+        match &selection {
+            Self::Tag(tag) if tag.is_empty() => Err("the tag is empty".to_owned()),
+            Self::Categories(categories) if categories.is_empty() => {
+                Err("there are no categories".to_owned())
+            }
+            _ => Ok(selection),
+        }
+    }
+}
+
+impl TryFrom<String> for EventSelection {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for EventSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::Tag(tag) => write!(f, "tag:{tag}"),
+            Self::Categories(categories) => write!(f, "categories:{}", categories.join(", ")),
+            Self::Regex(regex) => write!(f, "regex:{}", regex.as_str()),
+        }
+    }
+}
+
+impl EventSelection {
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Self::All => true,
+            Self::Tag(tag) => event.summary.contains(tag.as_str()),
+            Self::Categories(categories) => event.categories.iter().any(|c| {
+                categories
+                    .iter()
+                    .any(|wanted| wanted.eq_ignore_ascii_case(c))
+            }),
+            Self::Regex(regex) => regex.is_match(&event.summary),
+        }
+    }
+
+    /// The event as it's shown, without the tag that selected it
+    pub fn strip(&self, mut event: Event) -> Event {
+        if let Self::Tag(tag) = self {
+            event.summary = event
+                .summary
+                .replace(tag.as_str(), "")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        event
+    }
+}
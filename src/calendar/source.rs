@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::calendar::EventSelection;
+
+/// A calendar /events reads from, set up in the config
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalendarSource {
+    /// Shown as the choice in /events
+    pub name: String,
+    /// An ICS url, or the path of an ICS file
+    pub source: String,
+    #[serde(default = "CalendarSource::default_refresh_minutes")]
+    pub refresh_minutes: u64,
+    /// How far ahead recurring events are expanded into their occurrences
+    #[serde(default = "CalendarSource::default_lookahead_days")]
+    pub lookahead_days: i64,
+    /// Which events are shown, all of them when unset
+    #[serde(default, alias = "filter")]
+    pub select: EventSelection,
+}
+
+impl CalendarSource {
+    fn default_refresh_minutes() -> u64 {
+        60
+    }
+
+    fn default_lookahead_days() -> i64 {
+        90
+    }
+
+    /// The single calendar from `calendar.secret`, used when the config has none
+    pub fn legacy(url: impl Into<String>) -> Self {
+        Self {
+            name: "KN ALGO".to_owned(),
+            source: url.into(),
+            refresh_minutes: Self::default_refresh_minutes(),
+            lookahead_days: Self::default_lookahead_days(),
+            select: EventSelection::Tag("--BOT--".to_owned()),
+        }
+    }
+
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.refresh_minutes * 60)
+    }
+
+    pub fn lookahead(&self) -> chrono::Duration {
+        chrono::Duration::days(self.lookahead_days)
+    }
+
+    pub fn is_url(&self) -> bool {
+        self.source.starts_with("http://") || self.source.starts_with("https://")
+    }
+}
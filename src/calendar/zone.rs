@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use chrono::{
+    DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, ParseError, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use ical::parser::ical::component::{IcalTimeZone, IcalTimeZoneTransitionType};
+
+/// Parameters of an iCalendar property, as the ical crate gives them
+pub type Params = Option<Vec<(String, Vec<String>)>>;
+
+pub fn param<'a>(params: &'a Params, name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .flatten()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .and_then(|(_, values)| values.first())
+        .map(String::as_str)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Named(Tz),
+    /// A VTIMEZONE we couldn't match to a real timezone, so no daylight saving either
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Also understands vendor prefixed ids like "/mozilla.org/20050126_1/Europe/Warsaw"
+    fn named(name: &str) -> Option<Self> {
+        let name = name.trim().trim_matches('"');
+        let mut rest = name;
+        loop {
+            if let Ok(tz) = rest.parse::<Tz>() {
+                return Some(Self::Named(tz));
+            }
+            rest = &rest[rest.find('/')? + 1..];
+        }
+    }
+
+    fn from_vtimezone(timezone: &IcalTimeZone) -> Option<Self> {
+        let property = |props: &[ical::property::Property], name: &str| {
+            props
+                .iter()
+                .find(|p| p.name == name)
+                .and_then(|p| p.value.clone())
+        };
+
+        if let Some(zone) = ["TZID", "X-LIC-LOCATION"]
+            .iter()
+            .filter_map(|name| property(&timezone.properties, name))
+            .find_map(|name| Self::named(&name))
+        {
+            return Some(zone);
+        }
+
+        let standard = timezone
+            .transitions
+            .iter()
+            .find(|t| matches!(t.transition, IcalTimeZoneTransitionType::STANDARD))
+            .or(timezone.transitions.first())?;
+        property(&standard.properties, "TZOFFSETTO")
+            .and_then(|offset| parse_offset(&offset))
+            .map(Self::Fixed)
+    }
+
+    pub fn to_utc(self, naive: NaiveDateTime) -> DateTime<Utc> {
+        // a time skipped by the clocks moving forward happens an hour later
+        let local = |naive| match self {
+            Self::Named(tz) => tz
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|d| d.to_utc()),
+            Self::Fixed(offset) => offset
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|d| d.to_utc()),
+        };
+        local(naive)
+            .or_else(|| local(naive + Duration::hours(1)))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+    }
+
+    pub fn to_local(self, utc: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Named(tz) => utc.with_timezone(&tz).naive_local(),
+            Self::Fixed(offset) => utc.with_timezone(&offset).naive_local(),
+        }
+    }
+}
+
+/// "+0100", "-0530" or "+013000"
+fn parse_offset(offset: &str) -> Option<FixedOffset> {
+    let offset = offset.trim();
+    let sign = match offset.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = offset.get(1..)?;
+    let part = |range: std::ops::Range<usize>| digits.get(range)?.parse::<i32>().ok();
+    let seconds = match digits.len() {
+        4 => part(0..2)? * 3600 + part(2..4)? * 60,
+        6 => part(0..2)? * 3600 + part(2..4)? * 60 + part(4..6)?,
+        _ => return None,
+    };
+    FixedOffset::east_opt(sign * seconds)
+}
+
+/// The timezones a calendar's times can be in
+#[derive(Debug, Clone)]
+pub struct Zones {
+    /// For floating times, the calendar's X-WR-TIMEZONE
+    default: Tz,
+    /// Every VTIMEZONE by its TZID
+    defined: HashMap<String, Zone>,
+}
+
+impl Zones {
+    pub fn new(default: Tz, timezones: &[IcalTimeZone]) -> Self {
+        let defined = timezones
+            .iter()
+            .filter_map(|timezone| {
+                let tzid = timezone
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "TZID")?
+                    .value
+                    .clone()?;
+                Some((tzid, Zone::from_vtimezone(timezone)?))
+            })
+            .collect();
+
+        Self { default, defined }
+    }
+
+    /// The zone a time with these parameters is in
+    pub fn zone(&self, params: &Params) -> Zone {
+        param(params, "TZID")
+            .and_then(|tzid| {
+                self.defined
+                    .get(tzid)
+                    .copied()
+                    .or_else(|| Zone::named(tzid))
+            })
+            .unwrap_or(Zone::Named(self.default))
+    }
+
+    /// The time in UTC, and whether it's a whole day rather than a time
+    pub fn parse(&self, date: &str, params: &Params) -> Result<(DateTime<Utc>, bool), ParseError> {
+        let date = date.trim();
+        let zone = self.zone(params);
+        if param(params, "VALUE") == Some("DATE") || date.len() == 8 {
+            let naive = NaiveDate::parse_from_str(date, "%Y%m%d")?
+                .and_hms_opt(0, 0, 0)
+                .unwrap_or_default();
+            Ok((zone.to_utc(naive), true))
+        } else if date.ends_with('Z') {
+            let naive = NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%SZ")?;
+            Ok((Utc.from_utc_datetime(&naive), false))
+        } else {
+            let naive = NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%S")?;
+            Ok((zone.to_utc(naive), false))
+        }
+    }
+}
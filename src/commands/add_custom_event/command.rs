@@ -1,9 +1,14 @@
+use chrono::Utc;
 use serenity::{all::CreateCommand, async_trait};
 
 use crate::{
     aliases::Result,
-    commands::misc,
+    commands::{
+        dates::{self, DateConfirmation},
+        misc,
+    },
     components::CommandCtx,
+    permissions::Capability,
     traits::{BotCommand, Interactable},
 };
 
@@ -13,7 +18,7 @@ modal_macro::modal! {
             <input id="summary" style="paragraph">"Event Summary"</input>
         </row>
         <row>
-            <input id="when" style="short" min_len=14 max_len=14 placeholder="HH:MM DD-MM-YY">"Event start time"</input>
+            <input id="when" style="short" placeholder="tomorrow 18:00, pt 12:00, 18:00 24-12-26">"Event start time"</input>
         </row>
     </AddEventModal>
 }
@@ -25,16 +30,35 @@ impl BotCommand for AddEventCommand {
     async fn run(&self, ctx: &CommandCtx) -> Result {
         let result = ctx.modal::<AddEventModal>().await?;
 
-        let datetime = match misc::parse_date_dd_mm_yy(&result.when, "%H:%M %d-%m-%y") {
-            Ok(d) => d,
-            Err(_) => return result.respond("Invalid Date!", true).await,
+        let tz = misc::user_timezone(ctx.db, ctx.config, ctx.interaction.user.id).await?;
+        let start = match dates::parse_date(&result.when, tz, Utc::now()) {
+            Some(d) => d,
+            None => return result.respond("Invalid Date!", true).await,
         };
 
-        ctx.db.add_custom_event(&result.summary, datetime).await?;
+        if start.ambiguous {
+            let mut msg = dates::ask_date_confirmation(&result, start.when).await?;
+            msg.handle_events(ctx).await?;
+            let confirmed = msg
+                .clone_state::<DateConfirmation>()
+                .await
+                .is_some_and(|c| c.confirmed);
+            if confirmed {
+                ctx.db.add_custom_event(&result.summary, start.when).await?;
+                result.finish_deferred(dates::saved_response()).await?;
+            }
+            return Ok(());
+        }
+
+        ctx.db.add_custom_event(&result.summary, start.when).await?;
         result.respond("Done!", true).await
     }
 
     fn register(&self, create: CreateCommand) -> CreateCommand {
         create.description("Add a custom event")
     }
+
+    fn capability(&self) -> Capability {
+        Capability::Board
+    }
 }
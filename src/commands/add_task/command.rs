@@ -1,11 +1,16 @@
-use modal_macro::modal;
-use serenity::{all::CreateCommand, async_trait};
+use chrono::Utc;
+use modal_macro::{interactive_msg, modal, SelectionState};
+use serenity::{
+    all::{CreateCommand, CreateInteractionResponseMessage, RoleId, UserId},
+    async_trait,
+};
 
 use crate::{
-    aliases::Result,
-    commands::misc,
-    components::CommandCtx,
-    traits::{BotCommand, Interactable},
+    aliases::{Result, TypedResult},
+    commands::{add_task::embed::DeadlineEmbed, dates, misc},
+    components::{CommandCtx, EventCtx, InteractiveMessage},
+    database::Task,
+    traits::{BotCommand, Interactable, IntoResponse, StateTrait},
 };
 
 modal! {
@@ -17,32 +22,163 @@ modal! {
             <input id="description" style="paragraph">"Description"</input>
         </row>
         <row>
-            <input id="deadline" style="short" placeholder="DD-MM-YY" min_len=8 max_len=8>"Deadline"</input>
+            <input id="deadline" style="short" placeholder="tomorrow 18:00, pt 12:00, 24-12-26">"Deadline"</input>
+        </row>
+        <row>
+            <input id="repeat" style="short" required=false placeholder="weekly, every 2 weeks until DD-MM-YY">"Repeat"</input>
         </row>
     </AddTaskModal>
 }
 
+#[derive(SelectionState, Clone)]
+pub struct AssignState {
+    pub task: Task,
+    pub ambiguous_deadline: bool,
+    #[selection_state]
+    pub users: Vec<UserId>,
+    #[selection_state]
+    pub roles: Vec<RoleId>,
+}
+
+#[async_trait]
+impl StateTrait for AssignState {
+    async fn init(ctx: &CommandCtx) -> TypedResult<Self> {
+        Ok(Self {
+            task: Task::new("", "", Utc::now(), ctx.interaction.user.id),
+            ambiguous_deadline: false,
+            users: vec![],
+            roles: vec![],
+        })
+    }
+}
+
+interactive_msg! {
+    <AssignMsg handler=AssignHandler state=AssignState ephemeral=true>
+        <text>"Who should get this task?"</text>
+        <embed>DeadlineEmbed</embed>
+        <row>
+            <selection id="users" style=User placeholder="Users" min_values=0 max_values=25></selection>
+        </row>
+        <row>
+            <selection id="roles" style=Role placeholder="Roles" min_values=0 max_values=25></selection>
+        </row>
+        <row>
+            <button id="submit">"Ok"</button>
+            <button id="cancel" style="danger">"Cancel"</button>
+        </row>
+    </AssignMsg>
+}
+
+struct Summary(Task);
+
+impl IntoResponse for Summary {
+    fn into_response(&self) -> CreateInteractionResponseMessage {
+        let assigned = self
+            .0
+            .assigned_users
+            .iter()
+            .map(|id| format!("<@{}>", id))
+            .chain(self.0.assigned_roles.iter().map(|id| format!("<@&{}>", id)))
+            .collect::<Vec<_>>();
+
+        let assigned = if assigned.is_empty() {
+            "nobody yet".to_owned()
+        } else {
+            assigned.join(", ")
+        };
+
+        let repeats = match &self.0.recurrence {
+            Some(r) => format!("\nRepeats: {}", r),
+            None => String::new(),
+        };
+
+        CreateInteractionResponseMessage::new()
+            .content(format!(
+                "Task **{}** due <t:{}:f> assigned to {}{}",
+                self.0.title,
+                self.0.deadline.timestamp(),
+                assigned,
+                repeats
+            ))
+            .embeds(vec![])
+            .components(vec![])
+    }
+}
+
+struct Cancelled;
+
+impl IntoResponse for Cancelled {
+    fn into_response(&self) -> CreateInteractionResponseMessage {
+        CreateInteractionResponseMessage::new()
+            .content("Cancelled, nothing was saved")
+            .embeds(vec![])
+            .components(vec![])
+    }
+}
+
+#[async_trait]
+impl AssignHandlerTrait for AssignHandler {
+    async fn handle_submit(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<AssignState>().await.unwrap();
+
+        let mut task = state.task;
+        task.assigned_users = state.users;
+        task.assigned_roles = state.roles;
+        let task = ctx.db.add_task(&task).await?;
+
+        ctx.msg.stop();
+        ctx.edit(Summary(task)).await
+    }
+
+    async fn handle_cancel(ctx: &mut EventCtx) -> Result {
+        ctx.msg.stop();
+        ctx.edit(Cancelled).await
+    }
+}
+
 pub struct AddTaskCommand;
 
 #[async_trait]
 impl BotCommand for AddTaskCommand {
     async fn run(&self, ctx: &CommandCtx) -> Result {
         let result = ctx.modal::<AddTaskModal>().await?;
-        let datetime = match misc::parse_date_dd_mm_yy(&result.deadline, "%d-%m-%y") {
-            Ok(d) => d,
-            Err(_) => return result.respond("Invalid date!", true).await,
+        let tz = misc::user_timezone(ctx.db, ctx.config, ctx.interaction.user.id).await?;
+        let deadline = match dates::parse_date(&result.deadline, tz, Utc::now()) {
+            Some(d) => d,
+            None => return result.respond("Invalid date!", true).await,
         };
 
-        ctx.db
-            .add_task(
-                &result.title,
-                &result.description,
-                datetime,
-                result.interaction.user.id,
-            )
-            .await?;
+        let recurrence = if result.repeat.trim().is_empty() {
+            None
+        } else {
+            match misc::parse_recurrence(&result.repeat, tz) {
+                Some(r) => Some(r),
+                None => return result.respond("Invalid repeat rule!", true).await,
+            }
+        };
 
-        result.respond("Done!", true).await
+        let state = AssignState {
+            task: Task {
+                recurrence,
+                ..Task::new(
+                    &result.title,
+                    &result.description,
+                    deadline.when,
+                    result.interaction.user.id,
+                )
+            },
+            ambiguous_deadline: deadline.ambiguous,
+            users: vec![],
+            roles: vec![],
+        };
+        let embed = DeadlineEmbed::create(&state);
+        let mut msg = InteractiveMessage::from_interaction::<AssignMsg<AssignHandler>, _>(
+            &result,
+            state,
+            vec![embed],
+        )
+        .await?;
+        msg.handle_events(ctx).await
     }
 
     fn register(&self, create: CreateCommand) -> CreateCommand {
@@ -0,0 +1,45 @@
+use serenity::{all::CreateEmbed, async_trait};
+
+use crate::{
+    commands::add_task::command::AssignState,
+    components::{CommandCtx, EventCtx},
+    traits::into_embed::IntoEmbedInteractive,
+};
+
+pub struct DeadlineEmbed;
+
+impl DeadlineEmbed {
+    pub fn create(state: &AssignState) -> CreateEmbed {
+        let embed = CreateEmbed::new()
+            .color(serenity::model::colour::Color::MEIBE_PINK)
+            .title(state.task.title.clone())
+            .field(
+                "Deadline",
+                format!("<t:{0}:F> (<t:{0}:R>)", state.task.deadline.timestamp()),
+                false,
+            );
+
+        if state.ambiguous_deadline {
+            embed.field(
+                "Check the deadline",
+                "This is my best guess for the date you typed, cancel if it's wrong",
+                false,
+            )
+        } else {
+            embed
+        }
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for DeadlineEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<AssignState>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<AssignState>().await.unwrap();
+        Self::create(&state)
+    }
+}
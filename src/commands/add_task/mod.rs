@@ -1,3 +1,4 @@
 pub mod command;
+pub mod embed;
 
 pub use command::AddTaskCommand;
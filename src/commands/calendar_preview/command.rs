@@ -0,0 +1,128 @@
+use modal_macro::command;
+use serenity::{
+    all::{CreateCommand, CreateEmbed, CreateEmbedFooter, EditInteractionResponse},
+    async_trait,
+};
+
+use crate::{
+    aliases::Result,
+    calendar::EventSelection,
+    commands::misc,
+    components::CommandCtx,
+    permissions::Capability,
+    traits::{BotCommand, Interactable},
+};
+
+command! {
+    <CalendarPreview>
+        <description>"See which upcoming events of a calendar the bot shows"</description>
+        <option required=true choices=calendars>
+            <name>"calendar"</name>
+            <description>"The calendar to look at"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"selection"</name>
+            <description>"Try another one: all, tag:--BOT--, categories:Club, Meetings or regex:^\\[ALGO\\]"</description>
+            <option_type>String</option_type>
+        </option>
+    </CalendarPreview>
+}
+
+/// Events listed, a long calendar would go past what an embed can hold
+const PREVIEW_SIZE: usize = 25;
+/// Characters of each event's summary listed, so a full preview fits in the description
+const SUMMARY_LEN: usize = 100;
+
+pub struct CalendarPreviewCommand {
+    calendars: Vec<String>,
+}
+
+impl CalendarPreviewCommand {
+    pub fn new(calendars: Vec<String>) -> Self {
+        Self { calendars }
+    }
+}
+
+#[async_trait]
+impl BotCommand for CalendarPreviewCommand {
+    async fn run(&self, ctx: &CommandCtx) -> Result {
+        let options = CalendarPreview::options(ctx);
+        let tried = match options.selection.map(str::parse::<EventSelection>) {
+            None => None,
+            Some(Ok(s)) => Some(s),
+            Some(Err(e)) => {
+                return ctx
+                    .respond(format!("That selection doesn't work, {e}!"), true)
+                    .await
+            }
+        };
+
+        // the calendar might have to be fetched first
+        ctx.defer(true).await?;
+        let (calendar, configured) = match ctx.calendars.get_unselected(options.calendar).await {
+            None => {
+                return ctx
+                    .finish_deferred(EditInteractionResponse::new().content(format!(
+                        "The calendar \"{}\" isn't available right now!",
+                        options.calendar
+                    )))
+                    .await
+            }
+            Some(c) => c,
+        };
+        let selection = tried.unwrap_or(configured);
+
+        // the matches come first, so a busy calendar can't push them all out of the preview
+        let (matching, other): (Vec<_>, Vec<_>) =
+            calendar.events.iter().partition(|e| selection.matches(e));
+        let lines = matching
+            .iter()
+            .map(|event| ("✅", selection.strip((*event).clone())))
+            .chain(other.iter().map(|event| ("❌", (*event).clone())))
+            .take(PREVIEW_SIZE)
+            .map(|(mark, shown)| {
+                format!(
+                    "{mark} <t:{}:d> {}\n",
+                    shown.start.timestamp(),
+                    misc::truncate(&shown.summary, SUMMARY_LEN)
+                )
+            })
+            .collect::<String>();
+
+        let embed = CreateEmbed::new()
+            .color(serenity::model::Color::MEIBE_PINK)
+            .title(format!("Preview of {}", calendar.name))
+            .field("Selection", format!("`{selection}`"), true)
+            .field(
+                "Shown",
+                format!(
+                    "{} of {} upcoming events",
+                    matching.len(),
+                    calendar.events.len()
+                ),
+                true,
+            )
+            .description(match lines.is_empty() {
+                true => "No upcoming events".to_owned(),
+                false => lines,
+            })
+            .footer(CreateEmbedFooter::new(
+                "Change the calendar's \"select\" in the config to keep a selection",
+            ));
+
+        ctx.finish_deferred(EditInteractionResponse::new().embed(embed))
+            .await
+    }
+
+    fn register(&self, create: CreateCommand) -> CreateCommand {
+        CalendarPreview::new(
+            create,
+            &self.calendars[..self.calendars.len().min(misc::MAX_CHOICES)],
+        )
+    }
+
+    fn capability(&self) -> Capability {
+        Capability::Admin
+    }
+}
@@ -0,0 +1,388 @@
+use chrono::{
+    DateTime, Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use modal_macro::interactive_msg;
+use serenity::{
+    all::{CreateEmbed, EditInteractionResponse},
+    async_trait,
+};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    commands::misc,
+    components::{CommandCtx, EventCtx, InteractiveMessage, State},
+    traits::{into_embed::IntoEmbedInteractive, Interactable, StateTrait},
+};
+
+/// Deadlines given as a bare date are due by the end of that day
+const END_OF_DAY: NaiveTime = NaiveTime::from_hms_opt(23, 59, 0).unwrap();
+
+#[derive(Debug, Clone, Copy)]
+pub struct ParsedDate {
+    pub when: DateTime<Utc>,
+    /// The input could have meant another date, e.g. a bare weekday or time
+    pub ambiguous: bool,
+}
+
+const DATE_TIME_FORMATS: [&str; 8] = [
+    "%d-%m-%y %H:%M",
+    "%H:%M %d-%m-%y",
+    "%d-%m-%Y %H:%M",
+    "%d.%m.%y %H:%M",
+    "%d.%m.%Y %H:%M",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+];
+
+const DATE_FORMATS: [&str; 5] = ["%d-%m-%y", "%d-%m-%Y", "%d.%m.%y", "%d.%m.%Y", "%Y-%m-%d"];
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "monday" | "mon" | "poniedziałek" | "poniedzialek" | "pon" | "pn" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" | "wtorek" | "wt" => Weekday::Tue,
+        "wednesday" | "wed" | "środa" | "sroda" | "śr" | "sr" => Weekday::Wed,
+        "thursday" | "thu" | "thur" | "thurs" | "czwartek" | "czw" => Weekday::Thu,
+        "friday" | "fri" | "piątek" | "piatek" | "pt" => Weekday::Fri,
+        "saturday" | "sat" | "sobota" | "sob" | "sb" => Weekday::Sat,
+        "sunday" | "sun" | "niedziela" | "niedz" | "nd" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// Turns a local date and time in `tz` into an instant,
+/// a time skipped by a DST change is moved an hour later and counts as ambiguous
+fn resolve_local(naive: NaiveDateTime, tz: Tz, ambiguous: bool) -> Option<ParsedDate> {
+    if let Some(when) = tz.from_local_datetime(&naive).earliest() {
+        return Some(ParsedDate {
+            when: when.with_timezone(&Utc),
+            ambiguous,
+        });
+    }
+
+    tz.from_local_datetime(&(naive + Duration::hours(1)))
+        .earliest()
+        .map(|when| ParsedDate {
+            when: when.with_timezone(&Utc),
+            ambiguous: true,
+        })
+}
+
+/// Reads a date the way people type it, as a local time in `tz`:
+/// "24-12-26 18:00", ISO "2026-12-24T18:00", "tomorrow 18:00", "in 3 days", "in 2h",
+/// "next friday", Polish "jutro", "za 2 dni" or "pt 12:00".
+/// A date without a time means the end of that day.
+pub fn parse_date(input: &str, tz: Tz, now: DateTime<Utc>) -> Option<ParsedDate> {
+    let input = input.trim();
+    if let Ok(when) = DateTime::parse_from_rfc3339(input) {
+        return Some(ParsedDate {
+            when: when.with_timezone(&Utc),
+            ambiguous: false,
+        });
+    }
+
+    if let Some(naive) = DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        return resolve_local(naive, tz, false);
+    }
+
+    if let Some(date) = DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(input, format).ok())
+    {
+        return resolve_local(date.and_time(END_OF_DAY), tz, false);
+    }
+
+    let input = input.to_lowercase();
+    let mut words = input.split_whitespace().collect::<Vec<_>>();
+    let time = match words
+        .iter()
+        .position(|w| NaiveTime::parse_from_str(w, "%H:%M").is_ok())
+    {
+        Some(i) => Some(NaiveTime::parse_from_str(words.remove(i), "%H:%M").ok()?),
+        None => None,
+    };
+
+    // compact offsets like "2h", "in 30m" or "za 1w 2d" count from now, as reminder offsets do
+    let offset = match words.as_slice() {
+        ["in" | "za", offset @ ..] => offset,
+        offset => offset,
+    };
+    if time.is_none()
+        && !offset.is_empty()
+        && offset.iter().all(|w| misc::parse_offset(w).is_some())
+    {
+        return Some(ParsedDate {
+            when: now.checked_add_signed(misc::parse_offset(&offset.join(" "))?)?,
+            ambiguous: false,
+        });
+    }
+
+    let local_now = now.with_timezone(&tz).naive_local();
+    let today = local_now.date();
+    let mut ambiguous = false;
+
+    let date = match words.as_slice() {
+        [] => {
+            // a bare time is the next time the clock shows it
+            let time = time?;
+            ambiguous = true;
+            if today.and_time(time) > local_now {
+                today
+            } else {
+                today.succ_opt()?
+            }
+        }
+        ["today" | "dziś" | "dzis" | "dzisiaj"] => today,
+        ["tomorrow" | "jutro"] => today.succ_opt()?,
+        ["day", "after", "tomorrow"] | ["pojutrze"] => today.checked_add_days(Days::new(2))?,
+        ["in" | "za", amount, unit] => {
+            let amount = amount.parse::<u32>().ok()?;
+            match *unit {
+                "minute" | "minutes" | "min" | "mins" | "minut" | "minuty" | "minutę" => {
+                    return time.is_none().then(|| ParsedDate {
+                        when: now + Duration::minutes(amount.into()),
+                        ambiguous: false,
+                    });
+                }
+                "hour" | "hours" | "h" | "godzinę" | "godzine" | "godziny" | "godzin" => {
+                    return time.is_none().then(|| ParsedDate {
+                        when: now + Duration::hours(amount.into()),
+                        ambiguous: false,
+                    });
+                }
+                "day" | "days" | "d" | "dzień" | "dzien" | "dni" => {
+                    today.checked_add_days(Days::new(amount.into()))?
+                }
+                "week" | "weeks" | "w" | "tydzień" | "tydzien" | "tygodnie" | "tygodni" => {
+                    today.checked_add_days(Days::new(u64::from(amount) * 7))?
+                }
+                "month" | "months" | "miesiąc" | "miesiac" | "miesiące" | "miesiace"
+                | "miesięcy" | "miesiecy" => today.checked_add_months(Months::new(amount))?,
+                _ => return None,
+            }
+        }
+        [weekday] => {
+            // today still counts until its time has passed, "next" always skips it
+            let weekday = parse_weekday(weekday)?;
+            ambiguous = true;
+            let ahead =
+                (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+            let ahead = if ahead == 0 && today.and_time(time.unwrap_or(END_OF_DAY)) <= local_now {
+                7
+            } else {
+                ahead
+            };
+            today.checked_add_days(Days::new(ahead.into()))?
+        }
+        [
+            "next" | "następny" | "nastepny" | "następna" | "nastepna",
+            weekday,
+        ] => {
+            let weekday = parse_weekday(weekday)?;
+            ambiguous = true;
+            let ahead =
+                (6 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7
+                    + 1;
+            today.checked_add_days(Days::new(ahead.into()))?
+        }
+        _ => return None,
+    };
+
+    resolve_local(date.and_time(time.unwrap_or(END_OF_DAY)), tz, ambiguous)
+}
+
+/// Asks to confirm a guessed date, the answer ends up in the message's [`DateConfirmation`]
+pub async fn ask_date_confirmation<'a>(
+    interaction: &impl Interactable<'a>,
+    when: DateTime<Utc>,
+) -> TypedResult<InteractiveMessage> {
+    InteractiveMessage::from_interaction::<ConfirmDateMsg<ConfirmDateHandler>, _>(
+        interaction,
+        DateConfirmation {
+            when,
+            confirmed: false,
+        },
+        vec![DateConfirmation::embed(when)],
+    )
+    .await
+}
+
+#[derive(Clone, Debug)]
+pub struct DateConfirmation {
+    pub when: DateTime<Utc>,
+    pub confirmed: bool,
+}
+
+impl DateConfirmation {
+    pub fn embed(when: DateTime<Utc>) -> CreateEmbed {
+        CreateEmbed::new()
+            .color(serenity::model::Colour::MEIBE_PINK)
+            .title("Is this the date you meant?")
+            .description(format!("<t:{0}:F> (<t:{0}:R>)", when.timestamp()))
+    }
+}
+
+#[async_trait]
+impl StateTrait for DateConfirmation {
+    async fn init(_ctx: &CommandCtx) -> TypedResult<Self> {
+        Ok(Self {
+            when: Utc::now(),
+            confirmed: false,
+        })
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for DateConfirmation {
+    async fn from_command(_ctx: &CommandCtx, state: &State) -> CreateEmbed {
+        let state = state.clone::<Self>().await.unwrap();
+        Self::embed(state.when)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<Self>().await.unwrap();
+        Self::embed(state.when)
+    }
+}
+
+interactive_msg! {
+    <ConfirmDateMsg handler=ConfirmDateHandler state=DateConfirmation ephemeral=true>
+        <embed>DateConfirmation</embed>
+        <row>
+            <button id="confirm" style="success">"Yes"</button>
+            <button id="cancel" style="danger">"No"</button>
+        </row>
+    </ConfirmDateMsg>
+}
+
+interactive_msg! {
+    <CancelledMsg handler=CancelledHandler>
+        <text>"Cancelled, nothing was saved"</text>
+    </CancelledMsg>
+}
+
+impl CancelledHandlerTrait for CancelledHandler {}
+
+#[async_trait]
+impl ConfirmDateHandlerTrait for ConfirmDateHandler {
+    async fn handle_confirm(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<DateConfirmation>().await.unwrap();
+        state.confirmed = true;
+        ctx.msg.write_state(state).await;
+        ctx.msg.stop();
+        // the caller says it's saved once it is, see `saved_response`
+        ctx.acknowlage().await
+    }
+
+    async fn handle_cancel(ctx: &mut EventCtx) -> Result {
+        ctx.msg.stop();
+        ctx.update_msg::<CancelledMsg<CancelledHandler>>().await
+    }
+}
+
+/// Replaces a confirmed date question once the date is written, sent with `finish_deferred`
+pub fn saved_response() -> EditInteractionResponse {
+    EditInteractionResponse::new()
+        .content("Saved!")
+        .embeds(vec![])
+        .components(vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday, 12:00 in Warsaw
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 14, 10, 0, 0).unwrap()
+    }
+
+    fn parse(input: &str) -> Option<ParsedDate> {
+        parse_date(input, chrono_tz::Europe::Warsaw, now())
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn tomorrow_at_a_time() {
+        let date = parse("tomorrow 18:00").unwrap();
+        assert_eq!(date.when, utc(2026, 10, 15, 16, 0));
+        assert!(!date.ambiguous);
+        assert_eq!(parse("jutro 18:00").unwrap().when, date.when);
+    }
+
+    #[test]
+    fn next_weekday_is_the_coming_one() {
+        let date = parse("next friday").unwrap();
+        assert_eq!(date.when, utc(2026, 10, 16, 21, 59));
+        assert!(date.ambiguous);
+    }
+
+    #[test]
+    fn in_days_is_the_end_of_that_day() {
+        assert_eq!(parse("in 3 days").unwrap().when, utc(2026, 10, 17, 21, 59));
+        assert_eq!(parse("za 3 dni").unwrap().when, utc(2026, 10, 17, 21, 59));
+    }
+
+    #[test]
+    fn polish_weekday_with_a_time() {
+        let date = parse("pt 12:00").unwrap();
+        assert_eq!(date.when, utc(2026, 10, 16, 10, 0));
+        assert!(date.ambiguous);
+    }
+
+    #[test]
+    fn iso_is_local() {
+        // CET in December, an hour ahead of UTC
+        let date = parse("2026-12-24T18:00").unwrap();
+        assert_eq!(date.when, utc(2026, 12, 24, 17, 0));
+        assert!(!date.ambiguous);
+    }
+
+    #[test]
+    fn compact_offsets_count_from_now() {
+        assert_eq!(parse("in 2h").unwrap().when, now() + Duration::hours(2));
+        assert_eq!(parse("za 30m").unwrap().when, now() + Duration::minutes(30));
+        assert_eq!(parse("2h").unwrap().when, now() + Duration::hours(2));
+        assert_eq!(parse("in 1w 2d").unwrap().when, now() + Duration::days(9));
+        assert!(parse("in 2h 18:00").is_none());
+    }
+
+    #[test]
+    fn gibberish_is_refused() {
+        assert!(parse("someday").is_none());
+        assert!(parse("in 2 fortnights").is_none());
+        assert!(parse("").is_none());
+    }
+
+    #[test]
+    fn skipped_time_moves_an_hour_later() {
+        // clocks jump from 2:00 to 3:00 in Warsaw that night
+        let naive = NaiveDate::from_ymd_opt(2027, 3, 28)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let date = resolve_local(naive, chrono_tz::Europe::Warsaw, false).unwrap();
+        assert_eq!(date.when, utc(2027, 3, 28, 1, 30));
+        assert!(date.ambiguous);
+    }
+
+    #[test]
+    fn repeated_time_is_the_first_one() {
+        // clocks go back from 3:00 to 2:00 in Warsaw that night, 2:30 happens in CEST first
+        let naive = NaiveDate::from_ymd_opt(2026, 10, 25)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let date = resolve_local(naive, chrono_tz::Europe::Warsaw, false).unwrap();
+        assert_eq!(date.when, utc(2026, 10, 25, 0, 30));
+        assert!(!date.ambiguous);
+    }
+}
@@ -3,13 +3,25 @@ use serenity::{all::CreateCommand, async_trait};
 use crate::{
     aliases::{Result, TypedResult},
     calendar::Event,
+    commands::misc,
     components::{CommandCtx, InteractiveMessage},
     traits::{BotCommand, StateTrait},
 };
 
 use crate::commands::events::embed::Embed;
 use crate::{components::EventCtx, traits::Interactable};
-use modal_macro::interactive_msg;
+use modal_macro::{command, interactive_msg};
+
+command! {
+    <Events>
+        <description>"List upcoming events from the club's calendars"</description>
+        <option required=false choices=calendars>
+            <name>"calendar"</name>
+            <description>"Only this calendar, all of them by default"</description>
+            <option_type>String</option_type>
+        </option>
+    </Events>
+}
 
 #[derive(Clone)]
 pub struct State {
@@ -18,27 +30,33 @@ pub struct State {
     pub events: Vec<Event>,
 }
 
+impl State {
+    fn new(mut events: Vec<Event>) -> Self {
+        events.sort_unstable();
+        Self {
+            page: 0,
+            max: events.len(),
+            events,
+        }
+    }
+}
+
 #[async_trait]
 impl StateTrait for State {
+    /// Every calendar merged, with the custom events
     async fn init(ctx: &CommandCtx) -> TypedResult<Self> {
         let mut events = ctx
             .calendars
-            .get_calendar("KN ALGO")
+            .get_calendars()
             .await
-            .unwrap()
-            .events
-            .clone();
+            .iter()
+            .flat_map(|c| c.events.iter().cloned())
+            .collect::<Vec<_>>();
 
         let custom_events = ctx.db.get_custom_events().await?;
-
         events.extend(custom_events);
-        events.sort_unstable();
 
-        Ok(Self {
-            page: 0,
-            max: events.len(),
-            events,
-        })
+        Ok(Self::new(events))
     }
 }
 
@@ -87,16 +105,51 @@ impl HandlerTrait for Handler {
     }
 }
 
-pub struct EventsCommand;
+pub struct EventsCommand {
+    calendars: Vec<String>,
+}
+
+impl EventsCommand {
+    pub fn new(calendars: Vec<String>) -> Self {
+        Self { calendars }
+    }
+}
 
 #[async_trait]
 impl BotCommand for EventsCommand {
     async fn run(&self, ctx: &CommandCtx) -> Result {
-        let mut msg = InteractiveMessage::new::<AllEvents<Handler>>(ctx).await?;
+        let name = match Events::options(ctx).calendar {
+            None => {
+                let mut msg = InteractiveMessage::new::<AllEvents<Handler>>(ctx).await?;
+                return msg.handle_events(ctx).await;
+            }
+            Some(name) => name,
+        };
+
+        let calendar = match ctx.calendars.get_calendar(name).await {
+            None => {
+                return ctx
+                    .respond(
+                        format!("The calendar \"{}\" isn't available right now!", name),
+                        true,
+                    )
+                    .await
+            }
+            Some(c) => c,
+        };
+
+        let state = State::new(calendar.events.clone());
+        let embeds = vec![Embed::create(&state).await];
+        let mut msg =
+            InteractiveMessage::from_interaction::<AllEvents<Handler>, State>(ctx, state, embeds)
+                .await?;
         msg.handle_events(ctx).await
     }
 
     fn register(&self, create: CreateCommand) -> CreateCommand {
-        create.description("List all events in the google calendar")
+        Events::new(
+            create,
+            &self.calendars[..self.calendars.len().min(misc::MAX_CHOICES)],
+        )
     }
 }
@@ -1,7 +1,9 @@
-use crate::calendar::Event;
+use crate::calendar::{Event, EventStatus};
 use crate::commands::events::command::State;
+use crate::commands::misc;
 use crate::components::CommandCtx;
 use crate::components::EventCtx;
+use chrono::Duration;
 use serenity::all::CreateEmbedFooter;
 use serenity::{all::CreateEmbed, async_trait};
 
@@ -9,6 +11,12 @@ use crate::traits::{into_embed::IntoEmbedInteractive, IntoEmbed};
 
 pub struct Embed;
 
+/// Discord allows 4096, calendar descriptions are rarely worth a whole screen
+const MAX_DESCRIPTION: usize = 1000;
+/// Discord's limits, a longer title or field fails the whole message
+const MAX_TITLE: usize = 256;
+const MAX_FIELD: usize = 1024;
+
 impl IntoEmbed for Embed {
     fn into_embed() -> CreateEmbed {
         CreateEmbed::new().color(serenity::model::Color::MEIBE_PINK)
@@ -16,17 +24,60 @@ impl IntoEmbed for Embed {
 }
 
 impl Embed {
-    fn format_event(embed: CreateEmbed, event: &Event) -> CreateEmbed {
+    fn format_when(event: &Event) -> String {
+        let start = event.start.timestamp();
+        match (event.all_day, event.end) {
+            (true, Some(end)) if end - event.start > Duration::days(1) => {
+                // the end of a whole day event is the midnight after it
+                format!(
+                    "<t:{start}:D> – <t:{}:D>",
+                    (end - Duration::days(1)).timestamp()
+                )
+            }
+            (true, _) => format!("<t:{start}:D>"),
+            (false, Some(end)) if end - event.start < Duration::days(1) => {
+                format!("<t:{start}:F> – <t:{}:t>", end.timestamp())
+            }
+            (false, Some(end)) => format!("<t:{start}:F> – <t:{}:F>", end.timestamp()),
+            (false, None) => format!("<t:{start}:F>"),
+        }
+    }
+
+    pub fn format_event(embed: CreateEmbed, event: &Event) -> CreateEmbed {
+        let mut fields = vec![("When", Self::format_when(event), false)];
+        if let Some(location) = &event.location {
+            fields.push(("Where", misc::truncate(location, MAX_FIELD), false));
+        }
+        match event.status {
+            Some(EventStatus::Cancelled) => {
+                fields.push(("Status", "❌ Cancelled".to_owned(), true))
+            }
+            Some(EventStatus::Tentative) => {
+                fields.push(("Status", "❔ Tentative".to_owned(), true))
+            }
+            Some(EventStatus::Confirmed) | None => (),
+        }
+        if !event.categories.is_empty() {
+            let categories = event.categories.join(", ");
+            fields.push(("Categories", misc::truncate(&categories, MAX_FIELD), true));
+        }
+
+        let mut embed = embed
+            .fields(fields)
+            .title(misc::truncate(&event.summary, MAX_TITLE));
+        if let Some(description) = &event.description {
+            embed = embed.description(misc::truncate(description, MAX_DESCRIPTION));
+        }
+        // Discord refuses the embed over anything that isn't a web link
+        if let Some(url) = event.url.as_ref().filter(|url| {
+            reqwest::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+        }) {
+            embed = embed.url(url);
+        }
         embed
-            .fields(vec![(
-                "When",
-                format!("<t:{}:F>", event.start.timestamp()),
-                false,
-            )])
-            .title(event.summary.clone())
     }
 
-    async fn create(state: &State) -> CreateEmbed {
+    pub async fn create(state: &State) -> CreateEmbed {
         let embed = Self::into_embed();
         let embed = match state.events.get(state.page) {
             None => {
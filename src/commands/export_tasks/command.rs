@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use chrono_tz::Tz;
+use modal_macro::command;
+use serde::Serialize;
+use serenity::{
+    all::{CreateAttachment, CreateCommand, EditInteractionResponse, UserId},
+    async_trait,
+};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    commands::misc,
+    components::CommandCtx,
+    database::{Task, TaskFilter},
+    permissions::Capability,
+    traits::{BotCommand, Interactable},
+};
+
+command! {
+    <ExportTasks>
+        <description>"Download tasks as a CSV or JSON file"</description>
+        <option required=false>
+            <name>"format"</name>
+            <description>"csv (default) or json"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"giver"</name>
+            <description>"Only tasks given by this member"</description>
+            <option_type>User</option_type>
+        </option>
+        <option required=false>
+            <name>"assignee"</name>
+            <description>"Only tasks assigned to this member, directly or through a role"</description>
+            <option_type>User</option_type>
+        </option>
+        <option required=false>
+            <name>"from"</name>
+            <description>"Deadline on or after this day, e.g. 01-10-26"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"to"</name>
+            <description>"Deadline by this date, e.g. 31-01-27"</description>
+            <option_type>String</option_type>
+        </option>
+    </ExportTasks>
+}
+
+pub struct ExportTasksCommand;
+
+#[derive(Serialize)]
+struct ExportedReminder {
+    user: u64,
+    before: String,
+}
+
+/// One task as it ends up in the file, dates are in the exporter's timezone
+#[derive(Serialize)]
+struct ExportedTask {
+    id: i64,
+    title: String,
+    description: String,
+    status: String,
+    completed: bool,
+    deadline: String,
+    given_by: u64,
+    given_by_name: String,
+    assignees: Vec<u64>,
+    assignee_names: Vec<String>,
+    roles: Vec<u64>,
+    completed_by: Vec<u64>,
+    reminders: Vec<ExportedReminder>,
+}
+
+/// [`ExportedTask`] with the lists joined, CSV has no nesting
+#[derive(Serialize)]
+struct CsvTask<'t> {
+    id: i64,
+    title: &'t str,
+    description: &'t str,
+    status: &'t str,
+    completed: bool,
+    deadline: &'t str,
+    given_by: u64,
+    given_by_name: &'t str,
+    assignees: String,
+    assignee_names: String,
+    roles: String,
+    completed_by: String,
+    reminders: String,
+}
+
+impl<'t> From<&'t ExportedTask> for CsvTask<'t> {
+    fn from(task: &'t ExportedTask) -> Self {
+        let join = |ids: &[u64]| {
+            ids.iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(";")
+        };
+
+        Self {
+            id: task.id,
+            title: &task.title,
+            description: &task.description,
+            status: &task.status,
+            completed: task.completed,
+            deadline: &task.deadline,
+            given_by: task.given_by,
+            given_by_name: &task.given_by_name,
+            assignees: join(&task.assignees),
+            assignee_names: task.assignee_names.join(";"),
+            roles: join(&task.roles),
+            completed_by: join(&task.completed_by),
+            reminders: task
+                .reminders
+                .iter()
+                .map(|r| format!("{} {}", r.user, r.before))
+                .collect::<Vec<_>>()
+                .join(";"),
+        }
+    }
+}
+
+/// Display names of everyone mentioned in the tasks, looked up once each
+async fn user_names(ctx: &CommandCtx<'_>, tasks: &[Task]) -> HashMap<UserId, String> {
+    let mut users = tasks
+        .iter()
+        .flat_map(|t| t.assigned_users.iter().copied().chain([t.given_by]))
+        .collect::<Vec<_>>();
+    users.sort_unstable();
+    users.dedup();
+
+    let mut names = HashMap::new();
+    for user in users {
+        let name = match user.to_user(ctx.discord_ctx).await {
+            Ok(u) => u.global_name.unwrap_or(u.name),
+            Err(_) => user.to_string(),
+        };
+        names.insert(user, name);
+    }
+    names
+}
+
+async fn export(ctx: &CommandCtx<'_>, tasks: &[Task], tz: Tz) -> TypedResult<Vec<ExportedTask>> {
+    let ids = tasks.iter().map(|t| t.id).collect::<Vec<_>>();
+    let mut reminders = ctx.db.get_task_reminders(&ids).await?;
+    let names = user_names(ctx, tasks).await;
+    let name = |user: &UserId| names.get(user).cloned().unwrap_or_default();
+
+    Ok(tasks
+        .iter()
+        .map(|task| ExportedTask {
+            id: task.id,
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status.to_string(),
+            completed: task.completed,
+            deadline: task
+                .deadline
+                .with_timezone(&tz)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            given_by: task.given_by.get(),
+            given_by_name: name(&task.given_by),
+            assignees: task.assigned_users.iter().map(|u| u.get()).collect(),
+            assignee_names: task.assigned_users.iter().map(name).collect(),
+            roles: task.assigned_roles.iter().map(|r| r.get()).collect(),
+            completed_by: task.completed_by.iter().map(|u| u.get()).collect(),
+            reminders: reminders
+                .remove(&task.id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(user, before)| ExportedReminder {
+                    user: user.get(),
+                    before: misc::format_offset(before),
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+fn to_csv(tasks: &[ExportedTask]) -> TypedResult<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for task in tasks {
+        writer.serialize(CsvTask::from(task))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()).into())
+}
+
+#[async_trait]
+impl BotCommand for ExportTasksCommand {
+    async fn run(&self, ctx: &CommandCtx) -> Result {
+        let options = ExportTasks::options(ctx);
+        let json = match options.format.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("csv") => false,
+            Some("json") => true,
+            Some(_) => return ctx.respond("The format can be csv or json!", true).await,
+        };
+
+        let filter = TaskFilter {
+            giver: options.giver,
+            assignee: options.assignee,
+            ..Default::default()
+        };
+        let filter = match misc::task_filter(ctx, filter, options.from, options.to).await? {
+            Ok(f) => f,
+            Err(e) => return ctx.respond(e, true).await,
+        };
+
+        // looking up everyone's names can take longer than Discord waits for a response
+        ctx.defer(true).await?;
+        let tasks = ctx.db.search_tasks(&filter).await?;
+        let tz = misc::user_timezone(ctx.db, ctx.config, ctx.interaction.user.id).await?;
+        let exported = export(ctx, &tasks, tz).await?;
+
+        let file = if json {
+            CreateAttachment::bytes(serde_json::to_vec_pretty(&exported)?, "tasks.json")
+        } else {
+            CreateAttachment::bytes(to_csv(&exported)?, "tasks.csv")
+        };
+
+        ctx.finish_deferred(
+            EditInteractionResponse::new()
+                .content(format!("Exported {} tasks", exported.len()))
+                .new_attachment(file),
+        )
+        .await
+    }
+
+    fn register(&self, create: CreateCommand) -> CreateCommand {
+        ExportTasks::new(create)
+    }
+
+    fn capability(&self) -> Capability {
+        Capability::Board
+    }
+}
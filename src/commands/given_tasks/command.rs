@@ -1,15 +1,23 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
 use modal_macro::{interactive_msg, modal, SelectionState};
 use serenity::{
-    all::{CreateCommand, UserId},
+    all::{CreateCommand, RoleId, UserId},
     async_trait,
 };
 
 use crate::{
     aliases::{Result, TypedResult},
-    commands::{given_tasks::embed::Embed, misc},
+    commands::{
+        dates::{self, DateConfirmation},
+        given_tasks::embed::{AssigneesEmbed, Embed, HistoryEmbed},
+        misc,
+        task_details::{checklist, comments},
+    },
     components::{CommandCtx, EventCtx, InteractiveMessage},
-    database::Task,
-    error::BotError,
+    database::{HistoryEntry, ReminderGroup, Task, TaskStatus},
+    handler::notify::{notify_user, Notification},
     traits::{BotCommand, Interactable, StateTrait},
 };
 
@@ -24,16 +32,34 @@ modal! {
             <input id="description" style="paragraph" required=false placeholder="edit">"Description"</input>
         </row>
         <row>
-            <input id="deadline" style="short" required=false placeholder="edit (DD-MM-YY)" min_len=8 max_len=8>"Deadline"</input>
+            <input id="deadline" style="short" required=false placeholder="edit (tomorrow 18:00, pt 12:00, 24-12-26)">"Deadline"</input>
+        </row>
+        <row>
+            <input id="repeat" style="short" required=false placeholder="edit (weekly, every 2 weeks, none)">"Repeat"</input>
         </row>
     </EditTaskModal>
 }
 
-#[derive(Clone, Debug)]
+modal! {
+    <BlockersModal title="Blocked By" duration=600>
+        <row>
+            <input id="blockers" style="short" required=false placeholder="task ids, e.g. 3, 7 (empty clears)">"Blocking tasks"</input>
+        </row>
+    </BlockersModal>
+}
+
+#[derive(Clone, Debug, SelectionState)]
 pub struct State {
     pub page: usize,
     pub max_page: usize,
     pub tasks: Vec<Task>,
+    pub history: Vec<HistoryEntry>,
+    pub history_page: usize,
+    pub role_members: HashMap<RoleId, Vec<UserId>>,
+    #[selection_state]
+    pub selected: Vec<UserId>,
+    #[selection_state]
+    pub reassign_to: Vec<UserId>,
 }
 
 #[async_trait]
@@ -41,13 +67,38 @@ impl StateTrait for State {
     async fn init(ctx: &CommandCtx) -> TypedResult<Self> {
         let mut tasks = ctx.db.get_given_tasks(ctx.interaction.user.id).await?;
 
-        tasks.sort_unstable_by(|a, b| a.deadline.cmp(&b.deadline));
+        tasks.sort_unstable_by_key(|t| t.deadline);
+
+        let mut roles = tasks
+            .iter()
+            .flat_map(|t| t.assigned_roles.iter().copied())
+            .collect::<Vec<_>>();
+        roles.sort_unstable();
+        roles.dedup();
+
+        let role_members = misc::resolve_role_members(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.interaction.guild_id,
+            &roles,
+        )
+        .await?;
+
         Ok(Self {
             page: 0,
             max_page: tasks.len(),
             tasks,
+            history: vec![],
+            history_page: 0,
+            role_members,
+            selected: vec![],
+            reassign_to: vec![],
         })
     }
+
+    fn task_owner(&self) -> Option<UserId> {
+        self.tasks.get(self.page).map(|t| t.given_by)
+    }
 }
 
 interactive_msg! {
@@ -55,19 +106,61 @@ interactive_msg! {
         <embed>Embed</embed>
         <row>
             <button id="prev">"<"</button>
-            <button id="add_users" style="secondary">"+ Add Users"</button>
-            <button id="edit" style="success">"✏️"</button>
-            <button id="delete" style="danger">"🗑️"</button>
+            <button id="add_users" capability=TaskOwner style="secondary">"+ Add Users"</button>
+            <button id="edit" capability=TaskOwner style="success">"✏️"</button>
+            <button id="delete" capability=TaskOwner style="danger">"🗑️"</button>
             <button id="next">">"</button>
         </row>
+        <row>
+            <button id="approve" capability=TaskOwner style="success">"Approve"</button>
+            <button id="send_back" capability=TaskOwner style="danger">"Send Back"</button>
+            <button id="checklist" style="secondary">"📋 Checklist"</button>
+            <button id="add_comment" style="secondary">"💬"</button>
+            <button id="comments" style="secondary">"Comments"</button>
+        </row>
+        <row>
+            <button id="blockers" capability=TaskOwner style="secondary">"🔗 Blocked By"</button>
+            <button id="history" style="secondary">"📜 History"</button>
+            <button id="assignees" capability=TaskOwner style="secondary">"👥 Manage Assignees"</button>
+        </row>
     </GivenTasksMsg>
 }
 
+interactive_msg! {
+    <AssigneesMsg handler=AssigneesHandler state=State ephemeral=true>
+        <embed>AssigneesEmbed</embed>
+        <row>
+            <selection id="selected" style=User placeholder="People on the task" min_values=0 max_values=25></selection>
+        </row>
+        <row>
+            <selection id="reassign_to" style=User placeholder="Reassign to" min_values=0 max_values=1></selection>
+        </row>
+        <row>
+            <button id="remove" capability=TaskOwner style="danger">"Remove"</button>
+            <button id="reassign" capability=TaskOwner style="success">"Reassign"</button>
+            <button id="back">"Back"</button>
+        </row>
+    </AssigneesMsg>
+}
+
+interactive_msg! {
+    <HistoryMsg handler=HistoryHandler state=State ephemeral=true>
+        <embed>HistoryEmbed</embed>
+        <row>
+            <button id="prev_history">"<"</button>
+            <button id="back">"Back"</button>
+            <button id="next_history">">"</button>
+        </row>
+    </HistoryMsg>
+}
+
 #[derive(SelectionState, Clone)]
 struct AddUserState {
     pub task_id: i64,
     #[selection_state]
     pub users: Vec<UserId>,
+    #[selection_state]
+    pub roles: Vec<RoleId>,
 }
 
 #[async_trait]
@@ -76,6 +169,7 @@ impl StateTrait for AddUserState {
         Ok(Self {
             task_id: 0,
             users: vec![],
+            roles: vec![],
         })
     }
 }
@@ -83,7 +177,10 @@ impl StateTrait for AddUserState {
 interactive_msg! {
     <AddUsers handler=AddUserHandler state=AddUserState ephemeral=true>
         <row>
-            <selection id="users" style=User max_values=25></selection>
+            <selection id="users" style=User placeholder="Users" min_values=0 max_values=25></selection>
+        </row>
+        <row>
+            <selection id="roles" style=Role placeholder="Roles" min_values=0 max_values=25></selection>
         </row>
         <row>
             <button id="submit">"Ok"</button>
@@ -103,12 +200,96 @@ impl EmptyHandlerTrait for EmptyHandler {}
 impl AddUserHandlerTrait for AddUserHandler {
     async fn handle_submit(ctx: &mut EventCtx) -> Result {
         let state = ctx.msg.clone_state::<AddUserState>().await.unwrap();
-        ctx.db.add_users_to_task(state.task_id, state.users).await?;
+        let actor = ctx.interaction.user.id;
+        ctx.db
+            .add_users_to_task(state.task_id, state.users, actor)
+            .await?;
+        ctx.db
+            .add_roles_to_task(state.task_id, state.roles, actor)
+            .await?;
         ctx.msg.stop();
         ctx.update_msg::<EmptyMsg<EmptyHandler>>().await
     }
 }
 
+/// Lets the assignees of each task know nothing blocks it anymore
+async fn notify_unblocked(ctx: &EventCtx<'_>, tasks: Vec<(i64, String)>) -> Result {
+    for (id, title) in tasks {
+        let notification = Notification {
+            title: format!("Unblocked: {}", title),
+            description: "No unfinished task blocks this one anymore, you can start working on it"
+                .to_owned(),
+        };
+        let assignees = misc::task_assignees(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.db,
+            ctx.interaction.guild_id,
+            id,
+        )
+        .await?;
+        for user in assignees {
+            notify_user(
+                &ctx.discord_ctx.http,
+                ctx.config,
+                ctx.db,
+                user,
+                ReminderGroup::Tasks,
+                &notification,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn review(ctx: &mut EventCtx<'_>, to: TaskStatus, verdict: &str) -> Result {
+    let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+    let role_members = match state.tasks.get(state.page) {
+        None => return ctx.acknowlage().await,
+        Some(t) => task_role_members(&state, t),
+    };
+    let task = &mut state.tasks[state.page];
+
+    if task.status != TaskStatus::InReview {
+        return ctx
+            .respond("This task isn't waiting for review!", true)
+            .await;
+    }
+
+    task.status = to;
+    ctx.db
+        .set_task_status(task.id, to, &role_members, ctx.interaction.user.id)
+        .await?;
+    task.completed = to == TaskStatus::Done;
+    task.completed_by = match task.completed {
+        true => task.assignees(&state.role_members),
+        false => vec![],
+    };
+    if to == TaskStatus::Done {
+        notify_unblocked(ctx, ctx.db.get_unblocked_by(task.id).await?).await?;
+    }
+
+    let notification = Notification {
+        title: format!("{}: {}", verdict, task.title),
+        description: format!("<@{}> moved the task to {}", task.given_by, to),
+    };
+    for user in task.assignees(&state.role_members) {
+        notify_user(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.db,
+            user,
+            ReminderGroup::Tasks,
+            &notification,
+        )
+        .await?;
+    }
+
+    ctx.msg.write_state(state).await;
+    ctx.update_msg::<GivenTasksMsg<Handler>>().await
+}
+
 #[async_trait]
 impl HandlerTrait for Handler {
     async fn handle_prev(ctx: &mut EventCtx) -> Result {
@@ -142,6 +323,74 @@ impl HandlerTrait for Handler {
         ctx.update_msg::<GivenTasksMsg<Handler>>().await
     }
 
+    async fn handle_approve(ctx: &mut EventCtx) -> Result {
+        review(ctx, TaskStatus::Done, "Approved").await
+    }
+
+    async fn handle_send_back(ctx: &mut EventCtx) -> Result {
+        review(ctx, TaskStatus::InProgress, "Sent back").await
+    }
+
+    async fn handle_add_comment(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        match state.tasks.get(state.page) {
+            None => ctx.acknowlage().await,
+            Some(task) => comments::add(ctx, task).await,
+        }
+    }
+
+    async fn handle_history(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        let task = match state.tasks.get(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t,
+        };
+
+        state.history = ctx.db.get_task_history(task.id).await?;
+        state.history_page = 0;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<HistoryMsg<HistoryHandler>>().await
+    }
+
+    async fn handle_assignees(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if state.tasks.get(state.page).is_none() {
+            return ctx.acknowlage().await;
+        }
+
+        state.selected = vec![];
+        state.reassign_to = vec![];
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<AssigneesMsg<AssigneesHandler>>().await
+    }
+
+    async fn handle_comments(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        match state.tasks.get(state.page) {
+            None => ctx.acknowlage().await,
+            Some(task) => comments::open(ctx, task).await,
+        }
+    }
+
+    async fn handle_checklist(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        let task = match state.tasks.get(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t,
+        };
+        let task_id = task.id;
+
+        let checklist = checklist::open(ctx, task, Some(task.given_by)).await?;
+
+        // the task view shows the progress on its next update
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if let Some(task) = state.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.checklist = checklist;
+        }
+        ctx.msg.write_state(state).await;
+        Ok(())
+    }
+
     async fn handle_add_users(ctx: &mut EventCtx) -> Result {
         let state = ctx.msg.clone_state::<State>().await.unwrap();
         let task = match state.tasks.get(state.page) {
@@ -153,6 +402,7 @@ impl HandlerTrait for Handler {
             ctx,
             AddUserState {
                 users: vec![],
+                roles: vec![],
                 task_id: task.id,
             },
         )
@@ -177,23 +427,97 @@ impl HandlerTrait for Handler {
             task.description = result.description.clone();
         }
 
+        let tz = misc::user_timezone(ctx.db, ctx.config, ctx.interaction.user.id).await?;
+        let mut ambiguous = false;
         if !result.deadline.trim().is_empty() {
-            task.deadline = match misc::parse_date_dd_mm_yy(&result.deadline, "%d-%m-%y") {
-                Ok(d) => d,
-                Err(e) => match e {
-                    BotError::ChronoParse(_) => return result.respond("invalid date!", true).await,
-                    _ => unreachable!(),
-                },
+            let deadline = match dates::parse_date(&result.deadline, tz, Utc::now()) {
+                Some(d) => d,
+                None => return result.respond("invalid date!", true).await,
+            };
+            task.deadline = deadline.when;
+            ambiguous = deadline.ambiguous;
+        }
+
+        match result.repeat.trim().to_lowercase().as_str() {
+            "" => (),
+            "none" => task.recurrence = None,
+            repeat => match misc::parse_recurrence(repeat, tz) {
+                Some(r) => task.recurrence = Some(r),
+                None => return result.respond("Invalid repeat rule!", true).await,
+            },
+        }
+
+        if ambiguous {
+            let mut msg = dates::ask_date_confirmation(&result, task.deadline).await?;
+            msg.handle_events_from_event(ctx).await?;
+            let confirmed = msg
+                .clone_state::<DateConfirmation>()
+                .await
+                .is_some_and(|c| c.confirmed);
+            if confirmed {
+                ctx.db.edit_task(task, ctx.interaction.user.id).await?;
+                ctx.msg.write_state(state).await;
+                result.finish_deferred(dates::saved_response()).await?;
             }
+            return Ok(());
         }
 
-        ctx.db.edit_task(task).await?;
+        ctx.db.edit_task(task, ctx.interaction.user.id).await?;
 
         ctx.msg.write_state(state).await;
 
         result.respond("Edit Successful!", true).await
     }
 
+    async fn handle_blockers(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        let task = match state.tasks.get_mut(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t,
+        };
+
+        let result = ctx.modal::<BlockersModal>().await?;
+        let ids = match misc::parse_task_ids(&result.blockers) {
+            Some(ids) => ids,
+            None => return result.respond("Invalid task ids!", true).await,
+        };
+
+        if ctx.db.would_create_cycle(task.id, &ids).await? {
+            return result
+                .respond("That would make the tasks wait on each other!", true)
+                .await;
+        }
+
+        let was_blocked = task.is_blocked();
+        task.blocked_by = ctx
+            .db
+            .set_blockers(task.id, &ids, ctx.interaction.user.id)
+            .await?;
+        if was_blocked && !task.is_blocked() {
+            notify_unblocked(ctx, vec![(task.id, task.title.clone())]).await?;
+        }
+        let missing = ids
+            .iter()
+            .filter(|id| !task.blocked_by.iter().any(|b| b.id == **id))
+            .map(|id| format!("#{}", id))
+            .collect::<Vec<_>>();
+        ctx.msg.write_state(state).await;
+
+        if missing.is_empty() {
+            result.respond("Dependencies updated!", true).await
+        } else {
+            result
+                .respond(
+                    format!(
+                        "Dependencies updated, skipped tasks you didn't give and aren't assigned: {}",
+                        missing.join(", ")
+                    ),
+                    true,
+                )
+                .await
+        }
+    }
+
     async fn handle_delete(ctx: &mut EventCtx) -> Result {
         let mut state = ctx.msg.clone_state::<State>().await.unwrap();
         let task = match state.tasks.get(state.page) {
@@ -201,7 +525,13 @@ impl HandlerTrait for Handler {
             Some(t) => t,
         };
 
-        ctx.db.delete_task(task.id).await?;
+        // a finished blocker already let its tasks go when it was approved
+        let unblocked = match task.status {
+            TaskStatus::Done => vec![],
+            _ => ctx.db.get_unblocked_by(task.id).await?,
+        };
+        ctx.db.delete_task(task.id, ctx.interaction.user.id).await?;
+        notify_unblocked(ctx, unblocked).await?;
         state.tasks.remove(state.page);
         state.max_page -= 1;
         if state.page == state.max_page && state.page != 0 {
@@ -212,6 +542,210 @@ impl HandlerTrait for Handler {
     }
 }
 
+#[async_trait]
+impl HistoryHandlerTrait for HistoryHandler {
+    async fn handle_prev_history(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if state.history_page == 0 {
+            return ctx.acknowlage().await;
+        }
+
+        state.history_page -= 1;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<HistoryMsg<HistoryHandler>>().await
+    }
+
+    async fn handle_next_history(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if (state.history_page + 1) * misc::HISTORY_PER_PAGE >= state.history.len() {
+            return ctx.acknowlage().await;
+        }
+
+        state.history_page += 1;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<HistoryMsg<HistoryHandler>>().await
+    }
+
+    async fn handle_back(ctx: &mut EventCtx) -> Result {
+        ctx.update_msg::<GivenTasksMsg<Handler>>().await
+    }
+}
+
+/// Members of the task's roles, they count towards its completion too
+fn task_role_members(state: &State, task: &Task) -> Vec<UserId> {
+    let mut members = task
+        .assigned_roles
+        .iter()
+        .filter_map(|role| state.role_members.get(role))
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+    members.sort_unstable();
+    members.dedup();
+    members
+}
+
+/// Reloads the current task after its assignees changed and goes back to managing them
+async fn refresh_assignees(ctx: &mut EventCtx<'_>, mut state: State) -> Result {
+    let task_id = state.tasks[state.page].id;
+    if let Some(task) = ctx.db.get_tasks(&[task_id]).await?.pop() {
+        state.tasks[state.page] = task;
+    }
+    state.selected = vec![];
+    state.reassign_to = vec![];
+    ctx.msg.write_state(state).await;
+    ctx.update_msg::<AssigneesMsg<AssigneesHandler>>().await
+}
+
+#[async_trait]
+impl AssigneesHandlerTrait for AssigneesHandler {
+    async fn handle_remove(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        let task = match state.tasks.get(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t.clone(),
+        };
+
+        let removed = state
+            .selected
+            .iter()
+            .filter(|u| task.assigned_users.contains(u))
+            .copied()
+            .collect::<Vec<_>>();
+        if removed.is_empty() {
+            return ctx
+                .respond("Pick someone who's on the task first!", true)
+                .await;
+        }
+
+        let actor = ctx.interaction.user.id;
+        ctx.db
+            .remove_users_from_task(task.id, &removed, &task_role_members(&state, &task), actor)
+            .await?;
+
+        let notification = Notification {
+            title: format!("Removed from: {}", task.title),
+            description: format!("<@{}> took you off this task", actor),
+        };
+        for user in &removed {
+            notify_user(
+                &ctx.discord_ctx.http,
+                ctx.config,
+                ctx.db,
+                *user,
+                ReminderGroup::Tasks,
+                &notification,
+            )
+            .await?;
+        }
+
+        refresh_assignees(ctx, state).await
+    }
+
+    async fn handle_reassign(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        let task = match state.tasks.get(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t.clone(),
+        };
+
+        let (from, to) = match (state.selected.as_slice(), state.reassign_to.as_slice()) {
+            ([from], [to]) if task.assigned_users.contains(from) => (*from, *to),
+            _ => {
+                return ctx
+                    .respond(
+                        "Pick one person on the task and who should take it over!",
+                        true,
+                    )
+                    .await
+            }
+        };
+        if task.assigned_users.contains(&to) {
+            return ctx
+                .respond(format!("<@{}> is already on the task!", to), true)
+                .await;
+        }
+
+        let actor = ctx.interaction.user.id;
+        ctx.db
+            .reassign_task(task.id, from, to, &task_role_members(&state, &task), actor)
+            .await?;
+
+        notify_user(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.db,
+            from,
+            ReminderGroup::Tasks,
+            &Notification {
+                title: format!("Reassigned: {}", task.title),
+                description: format!("<@{}> handed this task over to <@{}>", actor, to),
+            },
+        )
+        .await?;
+        notify_user(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.db,
+            to,
+            ReminderGroup::Tasks,
+            &Notification {
+                title: format!("New task: {}", task.title),
+                description: format!(
+                    "<@{}> handed this task over to you from <@{}>, due <t:{}:f>. \
+                    Their reminders carry over",
+                    actor,
+                    from,
+                    task.deadline.timestamp()
+                ),
+            },
+        )
+        .await?;
+
+        refresh_assignees(ctx, state).await
+    }
+
+    async fn handle_back(ctx: &mut EventCtx) -> Result {
+        ctx.update_msg::<GivenTasksMsg<Handler>>().await
+    }
+}
+
+/// Opens the usual view on a single task the user gave, e.g. one picked from search results
+pub async fn open_task(ctx: &mut EventCtx<'_>, task_id: i64) -> Result {
+    let task = match ctx
+        .db
+        .get_given_tasks(ctx.interaction.user.id)
+        .await?
+        .into_iter()
+        .find(|t| t.id == task_id)
+    {
+        None => return ctx.respond("You didn't give that task!", true).await,
+        Some(t) => t,
+    };
+
+    let role_members = misc::resolve_role_members(
+        &ctx.discord_ctx.http,
+        ctx.config,
+        ctx.interaction.guild_id,
+        &task.assigned_roles,
+    )
+    .await?;
+
+    let state = State {
+        page: 0,
+        max_page: 1,
+        tasks: vec![task],
+        history: vec![],
+        history_page: 0,
+        role_members,
+        selected: vec![],
+        reassign_to: vec![],
+    };
+    let mut msg =
+        InteractiveMessage::from_event::<GivenTasksMsg<Handler>, State>(ctx, state).await?;
+    msg.handle_events_from_event(ctx).await
+}
+
 #[async_trait]
 impl BotCommand for GivenTasksCommand {
     async fn run(&self, ctx: &CommandCtx) -> Result {
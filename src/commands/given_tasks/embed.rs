@@ -1,12 +1,17 @@
+use std::collections::HashMap;
+
 use serenity::{
-    all::{CreateEmbed, CreateEmbedFooter},
+    all::{CreateEmbed, CreateEmbedFooter, RoleId, UserId},
     async_trait,
 };
 
 use crate::{
-    commands::given_tasks::command::State,
+    commands::{
+        given_tasks::command::State,
+        misc::{self, HISTORY_DIFF_PREVIEW_LEN, HISTORY_PER_PAGE},
+    },
     components::{CommandCtx, EventCtx},
-    database::Task,
+    database::{HistoryEntry, Task},
     traits::{into_embed::IntoEmbedInteractive, IntoEmbed},
 };
 
@@ -19,33 +24,81 @@ impl IntoEmbed for Embed {
 }
 
 impl Embed {
-    fn format_task(embed: CreateEmbed, task: &Task) -> CreateEmbed {
-        let targets_string = if task.assigned_users.is_empty() {
+    pub fn format_task(
+        embed: CreateEmbed,
+        task: &Task,
+        role_members: &HashMap<RoleId, Vec<UserId>>,
+    ) -> CreateEmbed {
+        let targets_string = if task.assigned_users.is_empty() && task.assigned_roles.is_empty() {
             "No people assigned".to_owned()
         } else {
             task.assigned_users
                 .iter()
                 .map(|id| format!("<@{}> ", id))
+                .chain(task.assigned_roles.iter().map(|id| format!("<@&{}> ", id)))
+                .collect::<String>()
+        };
+
+        let assignees = task.assignees(role_members);
+        let done = assignees
+            .iter()
+            .filter(|u| task.completed_by.contains(u))
+            .count();
+        let progress_string = if assignees.is_empty() {
+            "Nobody to complete it".to_owned()
+        } else {
+            assignees
+                .iter()
+                .map(|id| {
+                    let mark = if task.completed_by.contains(id) {
+                        "✅"
+                    } else {
+                        "❌"
+                    };
+                    format!("{} <@{}>\n", mark, id)
+                })
                 .collect::<String>()
         };
-        embed.title(task.title.clone()).fields(vec![
-            ("Description", task.description.clone(), false),
-            (
-                "Deadline",
-                format!("<t:{}:D>", task.deadline.timestamp()),
-                true,
-            ),
-            (
-                "Completed",
-                if task.completed {
-                    "Yes".to_owned()
-                } else {
-                    "No".to_owned()
-                },
-                true,
-            ),
-            ("Assigned People", targets_string, false),
-        ])
+
+        let embed = if task.is_overdue() {
+            embed.color(serenity::model::colour::Color::RED)
+        } else {
+            embed
+        };
+
+        embed
+            .title(task.title.clone())
+            .fields(vec![
+                ("Description", task.description.clone(), false),
+                ("Deadline", misc::format_deadline(task), true),
+                (
+                    "Completed",
+                    format!("{}/{} done", done, assignees.len()),
+                    true,
+                ),
+                ("Status", task.status.to_string(), true),
+                ("Assigned People", targets_string, false),
+                ("Progress", progress_string, false),
+            ])
+            .fields((!task.blocked_by.is_empty()).then(|| {
+                (
+                    "Blocked by",
+                    task.blocked_by
+                        .iter()
+                        .map(|b| format!("{}\n", b))
+                        .collect::<String>(),
+                    false,
+                )
+            }))
+            .fields(
+                task.checklist_progress()
+                    .map(|progress| ("Checklist", progress, true)),
+            )
+            .fields(
+                task.recurrence
+                    .iter()
+                    .map(|r| ("Repeats", r.to_string(), false)),
+            )
     }
 
     fn create(state: &State) -> CreateEmbed {
@@ -53,13 +106,14 @@ impl Embed {
 
         let embed = match state.tasks.get(state.page) {
             None => return embed.field("Task", "No Tasks", false),
-            Some(t) => Self::format_task(embed, t),
+            Some(t) => Self::format_task(embed, t, &state.role_members),
         };
 
         embed.footer(CreateEmbedFooter::new(format!(
-            "{}/{}",
+            "{}/{} • Task #{}",
             state.page + 1,
-            state.max_page
+            state.max_page,
+            state.tasks[state.page].id
         )))
     }
 }
@@ -76,3 +130,123 @@ impl IntoEmbedInteractive for Embed {
         Self::create(&state)
     }
 }
+
+pub struct HistoryEmbed;
+
+impl HistoryEmbed {
+    fn create(state: &State) -> CreateEmbed {
+        let embed = Embed::into_embed();
+
+        let task = match state.tasks.get(state.page) {
+            None => return embed.field("Task", "No Tasks", false),
+            Some(t) => t,
+        };
+
+        let embed = embed.title(format!("History: {}", task.title));
+        if state.history.is_empty() {
+            return embed.description("Nothing recorded yet");
+        }
+
+        let entries = state
+            .history
+            .iter()
+            .skip(state.history_page * HISTORY_PER_PAGE)
+            .take(HISTORY_PER_PAGE)
+            .map(|entry| {
+                let preview = HistoryEntry {
+                    before: entry
+                        .before
+                        .as_deref()
+                        .map(|b| misc::truncate(b, HISTORY_DIFF_PREVIEW_LEN)),
+                    after: entry
+                        .after
+                        .as_deref()
+                        .map(|a| misc::truncate(a, HISTORY_DIFF_PREVIEW_LEN)),
+                    ..entry.clone()
+                };
+                format!("{}\n\n", preview)
+            })
+            .collect::<String>();
+
+        embed
+            .description(entries)
+            .footer(CreateEmbedFooter::new(format!(
+                "{}/{}",
+                state.history_page + 1,
+                state.history.len().div_ceil(HISTORY_PER_PAGE)
+            )))
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for HistoryEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<State>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        Self::create(&state)
+    }
+}
+
+pub struct AssigneesEmbed;
+
+impl AssigneesEmbed {
+    fn create(state: &State) -> CreateEmbed {
+        let embed = Embed::into_embed();
+
+        let task = match state.tasks.get(state.page) {
+            None => return embed.field("Task", "No Tasks", false),
+            Some(t) => t,
+        };
+
+        let users = if task.assigned_users.is_empty() {
+            "Nobody assigned directly".to_owned()
+        } else {
+            task.assigned_users
+                .iter()
+                .map(|id| {
+                    let mark = if task.completed_by.contains(id) {
+                        "✅"
+                    } else {
+                        "❌"
+                    };
+                    format!("{} <@{}>\n", mark, id)
+                })
+                .collect::<String>()
+        };
+
+        embed
+            .title(format!("Assignees: {}", task.title))
+            .description(
+                "Pick people to take them off the task, \
+                or pick one of them and who takes over to reassign it",
+            )
+            .field("People", users, false)
+            .fields((!task.assigned_roles.is_empty()).then(|| {
+                (
+                    "Roles",
+                    task.assigned_roles
+                        .iter()
+                        .map(|id| format!("<@&{}> ", id))
+                        .collect::<String>(),
+                    false,
+                )
+            }))
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for AssigneesEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<State>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        Self::create(&state)
+    }
+}
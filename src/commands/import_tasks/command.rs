@@ -0,0 +1,218 @@
+use chrono::Utc;
+use chrono_tz::Tz;
+use modal_macro::command;
+use serde::Deserialize;
+use serenity::{
+    all::{CreateCommand, EditInteractionResponse, UserId},
+    async_trait,
+};
+
+use crate::{
+    aliases::Result,
+    commands::{dates, misc},
+    components::CommandCtx,
+    database::Task,
+    permissions::Capability,
+    traits::{BotCommand, Interactable},
+};
+
+command! {
+    <ImportTasks>
+        <description>"Create tasks from a CSV or JSON file"</description>
+        <option required=true>
+            <name>"file"</name>
+            <description>"Rows with title, description, deadline and assignees (user ids)"</description>
+            <option_type>Attachment</option_type>
+        </option>
+    </ImportTasks>
+}
+
+pub struct ImportTasksCommand;
+
+/// Bigger files are surely a mistake
+const MAX_FILE_SIZE: u32 = 1024 * 1024;
+/// Errors listed in the response and characters shown of each, so it fits in a message
+const MAX_ERRORS_SHOWN: usize = 15;
+const MAX_ERROR_LEN: usize = 100;
+
+/// Assignees as a JSON list of ids or as text, which is what a CSV cell holds.
+/// A cell with just one id is read as a number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Assignees {
+    Ids(Vec<u64>),
+    Id(u64),
+    Text(String),
+}
+
+impl Default for Assignees {
+    fn default() -> Self {
+        Self::Ids(vec![])
+    }
+}
+
+/// A row of the file, same columns as an export so one can be edited and imported back.
+/// Columns that aren't listed here are ignored.
+#[derive(Deserialize)]
+struct ImportedTask {
+    title: String,
+    #[serde(default)]
+    description: String,
+    deadline: String,
+    #[serde(default)]
+    assignees: Assignees,
+}
+
+impl ImportedTask {
+    fn validate(
+        self,
+        tz: Tz,
+        given_by: UserId,
+    ) -> std::result::Result<(Task, Vec<UserId>), String> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err("the title is empty".to_owned());
+        }
+
+        let deadline = match dates::parse_date(&self.deadline, tz, Utc::now()) {
+            None => return Err(format!("couldn't read the deadline \"{}\"", self.deadline)),
+            Some(d) if d.ambiguous => {
+                return Err(format!(
+                    "the deadline \"{}\" could mean more than one date, write it out in full",
+                    self.deadline
+                ));
+            }
+            Some(d) => d.when,
+        };
+
+        let assignees = match self.assignees {
+            Assignees::Ids(ids) if ids.contains(&0) => None,
+            Assignees::Ids(ids) => Some(ids.into_iter().map(UserId::new).collect()),
+            Assignees::Id(0) => None,
+            Assignees::Id(id) => Some(vec![UserId::new(id)]),
+            Assignees::Text(text) => misc::parse_user_ids(&text),
+        };
+        let assignees = match assignees {
+            None => return Err("the assignees should be user ids".to_owned()),
+            Some(a) => a,
+        };
+
+        Ok((
+            Task::new(title, self.description.trim(), deadline, given_by),
+            assignees,
+        ))
+    }
+}
+
+/// Every row of the file numbered from 1, the ones that couldn't be read as an error
+fn read_rows(
+    data: &[u8],
+    json: bool,
+) -> std::result::Result<Vec<std::result::Result<ImportedTask, String>>, String> {
+    if json {
+        let rows = serde_json::from_slice::<Vec<serde_json::Value>>(data)
+            .map_err(|e| format!("The file isn't a JSON list of tasks: {e}"))?;
+        return Ok(rows
+            .into_iter()
+            .map(|row| serde_json::from_value(row).map_err(|e| e.to_string()))
+            .collect());
+    }
+
+    let mut reader = csv::Reader::from_reader(data);
+    Ok(reader
+        .deserialize()
+        .map(|row| {
+            row.map_err(|e| match e.kind() {
+                csv::ErrorKind::Deserialize { err, .. } => err.to_string(),
+                _ => e.to_string(),
+            })
+        })
+        .collect())
+}
+
+#[async_trait]
+impl BotCommand for ImportTasksCommand {
+    async fn run(&self, ctx: &CommandCtx) -> Result {
+        let id = ImportTasks::options(ctx).file;
+        let attachment = match ctx.interaction.data.resolved.attachments.get(&id) {
+            None => return ctx.respond("Couldn't find the uploaded file!", true).await,
+            Some(a) => a,
+        };
+        if attachment.size > MAX_FILE_SIZE {
+            return ctx.respond("The file is too big!", true).await;
+        }
+        let json = match attachment.filename.rsplit('.').next() {
+            Some("json") => true,
+            Some("csv") => false,
+            _ => return ctx.respond("Upload a .csv or .json file!", true).await,
+        };
+
+        ctx.defer(true).await?;
+        let data = attachment.download().await?;
+
+        let rows = match read_rows(&data, json) {
+            Err(e) => {
+                return ctx
+                    .finish_deferred(EditInteractionResponse::new().content(e))
+                    .await
+            }
+            Ok(rows) => rows,
+        };
+        if rows.is_empty() {
+            return ctx
+                .finish_deferred(EditInteractionResponse::new().content("The file has no tasks!"))
+                .await;
+        }
+
+        let user = ctx.interaction.user.id;
+        let tz = misc::user_timezone(ctx.db, ctx.config, user).await?;
+        let mut tasks = vec![];
+        let mut errors = vec![];
+        for (i, row) in rows.into_iter().enumerate() {
+            match row.and_then(|row| row.validate(tz, user)) {
+                Ok(task) => tasks.push(task),
+                Err(e) => errors.push(format!(
+                    "Row {}: {}",
+                    i + 1,
+                    misc::truncate(&e, MAX_ERROR_LEN)
+                )),
+            }
+        }
+
+        // nothing is imported until the whole file is right, so it can just be fixed and uploaded again
+        if !errors.is_empty() {
+            let mut content = format!(
+                "Nothing was imported, {} rows have errors:\n{}",
+                errors.len(),
+                errors
+                    .iter()
+                    .take(MAX_ERRORS_SHOWN)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+            if errors.len() > MAX_ERRORS_SHOWN {
+                content += &format!("\n...and {} more", errors.len() - MAX_ERRORS_SHOWN);
+            }
+            return ctx
+                .finish_deferred(EditInteractionResponse::new().content(content))
+                .await;
+        }
+
+        let count = tasks.len();
+        ctx.db.import_tasks(&tasks, user).await?;
+
+        ctx.finish_deferred(
+            EditInteractionResponse::new().content(format!("Imported {} tasks", count)),
+        )
+        .await
+    }
+
+    fn register(&self, create: CreateCommand) -> CreateCommand {
+        ImportTasks::new(create)
+    }
+
+    fn capability(&self) -> Capability {
+        Capability::Board
+    }
+}
@@ -1,11 +1,351 @@
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
 
-use crate::aliases::TypedResult;
+use chrono::{Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serenity::{
+    all::{GuildId, Http, Member, RoleId, UserId},
+    futures::TryStreamExt,
+};
 
-pub fn parse_date_dd_mm_yy(date: &str, format: &str) -> TypedResult<DateTime<Utc>> {
-    let naive = NaiveDate::parse_from_str(date, format)?;
-    let naive_date = naive.and_hms_opt(0, 0, 0).unwrap();
-    Ok(Utc.from_utc_datetime(&naive_date))
+use crate::{
+    aliases::TypedResult,
+    commands::dates,
+    components::CommandCtx,
+    config::Config,
+    database::{Db, Recurrence, RecurrenceUnit, Task, TaskFilter},
+};
+
+pub const COMMENTS_PER_PAGE: usize = 5;
+/// Characters of a comment shown in the list, so a full page fits in an embed description
+pub const COMMENT_PREVIEW_LEN: usize = 700;
+pub const HISTORY_PER_PAGE: usize = 5;
+/// Characters of each side of a history diff shown, so a full page fits in an embed description
+pub const HISTORY_DIFF_PREVIEW_LEN: usize = 300;
+pub const SEARCH_RESULTS_PER_PAGE: usize = 5;
+/// Reminders a user can have on one task, one delete button each
+pub const MAX_REMINDERS: usize = 5;
+/// Longest time before a deadline a reminder can be set for
+pub const MAX_OFFSET: Duration = Duration::weeks(52);
+/// Discord doesn't take more choices for a command option than this
+pub const MAX_CHOICES: usize = 25;
+
+/// The user's own timezone or the configured default
+pub async fn user_timezone(db: &Db, config: &Config, user: UserId) -> TypedResult<Tz> {
+    Ok(db.get_user_timezone(user).await?.unwrap_or(config.timezone))
+}
+
+/// Fills in the deadline range and the assignee's roles of a filter built from command options.
+/// A date that can't be read is reported back as the error message.
+pub async fn task_filter(
+    ctx: &CommandCtx<'_>,
+    mut filter: TaskFilter,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> TypedResult<std::result::Result<TaskFilter, String>> {
+    let tz = user_timezone(ctx.db, ctx.config, ctx.interaction.user.id).await?;
+    let now = Utc::now();
+
+    if let Some(input) = from {
+        filter.from = match dates::parse_date(input, tz, now) {
+            // whole days, so a bare date doesn't skip everything due before its end
+            Some(date) => tz
+                .from_local_datetime(
+                    &date
+                        .when
+                        .with_timezone(&tz)
+                        .date_naive()
+                        .and_time(NaiveTime::MIN),
+                )
+                .earliest()
+                .map(|d| d.with_timezone(&Utc)),
+            None => return Ok(Err(format!("Couldn't read the date \"{}\"!", input))),
+        };
+    }
+
+    if let Some(input) = to {
+        filter.to = match dates::parse_date(input, tz, now) {
+            Some(date) => Some(date.when),
+            None => return Ok(Err(format!("Couldn't read the date \"{}\"!", input))),
+        };
+    }
+
+    // someone who left the guild keeps only their direct assignments
+    if let (Some(user), Some(guild_id)) = (filter.assignee, ctx.interaction.guild_id) {
+        filter.assignee_roles = guild_id
+            .member(ctx.discord_ctx, user)
+            .await
+            .map(|m| m.roles)
+            .unwrap_or_default();
+    }
+
+    Ok(Ok(filter))
+}
+
+/// A task's deadline as shown in its embed, marked when it has passed without the task being done
+pub fn format_deadline(task: &Task) -> String {
+    let deadline = format!("<t:{0}:f> (<t:{0}:R>)", task.deadline.timestamp());
+    if task.is_overdue() {
+        format!("⚠️ Overdue\n{}", deadline)
+    } else {
+        deadline
+    }
+}
+
+/// At most `max` characters, with an ellipsis when something was cut off
+pub fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max.saturating_sub(1)) {
+        Some((end, _)) if text.chars().count() > max => format!("{}…", &text[..end]),
+        _ => text.to_owned(),
+    }
+}
+
+/// How long before something an offset is, e.g. "1w 2d", "3h 30m"
+pub fn format_offset(offset: Duration) -> String {
+    let minutes = offset.num_minutes();
+    let parts = [
+        (minutes / (7 * 24 * 60), "w"),
+        (minutes / (24 * 60) % 7, "d"),
+        (minutes / 60 % 24, "h"),
+        (minutes % 60, "m"),
+    ]
+    .into_iter()
+    .filter(|(n, _)| *n != 0)
+    .map(|(n, unit)| format!("{n}{unit}"))
+    .collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        "0m".to_owned()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Reads offsets like "2h", "30m" or "1w 2d", the other way around from [`format_offset`].
+/// Anything longer than [`MAX_OFFSET`] is refused.
+pub fn parse_offset(input: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in input.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let n = number.parse::<i64>().ok()?;
+        number.clear();
+        total = total.checked_add(&match c {
+            'w' => Duration::try_weeks(n)?,
+            'd' => Duration::try_days(n)?,
+            'h' => Duration::try_hours(n)?,
+            'm' => Duration::try_minutes(n)?,
+            _ => return None,
+        })?;
+    }
+
+    // a number without a unit at the end
+    if !number.is_empty() || total <= Duration::zero() || total > MAX_OFFSET {
+        return None;
+    }
+    Some(total)
+}
+
+/// User ids separated by commas, semicolons or spaces, mentions like "<@123>" work too
+pub fn parse_user_ids(input: &str) -> Option<Vec<UserId>> {
+    input
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.trim_start_matches("<@")
+                .trim_start_matches('!')
+                .trim_end_matches('>')
+                .parse::<u64>()
+                .ok()
+                .filter(|id| *id != 0)
+                .map(UserId::new)
+        })
+        .collect()
+}
+
+/// One checklist item per non-empty line
+pub fn parse_checklist_items(input: &str) -> Vec<String> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Task ids separated by commas or spaces, "#12" works too
+pub fn parse_task_ids(input: &str) -> Option<Vec<i64>> {
+    let mut ids = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|id| !id.is_empty())
+        .map(|id| id.trim_start_matches('#').parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    ids.sort_unstable();
+    ids.dedup();
+    Some(ids)
+}
+
+/// Parses "daily", "weekly", "monthly" or "every [N] days/weeks/months",
+/// optionally followed by "until <date>"
+pub fn parse_recurrence(input: &str, tz: Tz) -> Option<Recurrence> {
+    let input = input.trim().to_lowercase();
+    let (rule, until) = match input.split_once(" until ") {
+        Some((rule, until)) => (
+            rule.trim(),
+            Some(dates::parse_date(until, tz, Utc::now())?.when),
+        ),
+        None => (input.as_str(), None),
+    };
+
+    let (interval, unit) = match rule {
+        "daily" => (1, RecurrenceUnit::Days),
+        "weekly" => (1, RecurrenceUnit::Weeks),
+        "monthly" => (1, RecurrenceUnit::Months),
+        _ => {
+            let mut words = rule.split_whitespace();
+            if words.next()? != "every" {
+                return None;
+            }
+
+            let mut unit = words.next()?;
+            let interval = match unit.parse::<u32>() {
+                Ok(n) => {
+                    unit = words.next()?;
+                    n
+                }
+                Err(_) => 1,
+            };
+
+            if interval == 0 || words.next().is_some() {
+                return None;
+            }
+
+            let unit = match unit {
+                "day" | "days" => RecurrenceUnit::Days,
+                "week" | "weeks" => RecurrenceUnit::Weeks,
+                "month" | "months" => RecurrenceUnit::Months,
+                _ => return None,
+            };
+
+            (interval, unit)
+        }
+    };
+
+    Some(Recurrence {
+        interval,
+        unit,
+        until,
+    })
+}
+
+pub async fn role_members(
+    http: &Http,
+    guild_id: GuildId,
+    roles: &[RoleId],
+) -> TypedResult<HashMap<RoleId, Vec<UserId>>> {
+    let mut members: HashMap<RoleId, Vec<UserId>> = HashMap::new();
+    if roles.is_empty() {
+        return Ok(members);
+    }
+
+    let mut stream = Box::pin(guild_id.members_iter(http));
+    while let Some(member) = stream.try_next().await? {
+        if member.user.bot {
+            continue;
+        }
+
+        for role in member.roles.iter().filter(|r| roles.contains(r)) {
+            members.entry(*role).or_default().push(member.user.id);
+        }
+    }
+
+    Ok(members)
+}
+
+/// Members of each of the roles, in the guild the command was used in.
+/// Without one, e.g. in a DM or the hourly job, in the configured `tasks_guild`,
+/// or in every guild the bot is in when that's unset.
+pub async fn resolve_role_members(
+    http: &Http,
+    config: &Config,
+    guild_id: Option<GuildId>,
+    roles: &[RoleId],
+) -> TypedResult<HashMap<RoleId, Vec<UserId>>> {
+    if roles.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    if let Some(guild_id) = guild_id.or(config.tasks_guild) {
+        return role_members(http, guild_id, roles).await;
+    }
+
+    let mut members: HashMap<RoleId, Vec<UserId>> = HashMap::new();
+    for guild in http.get_guilds(None, None).await? {
+        for (role, users) in role_members(http, guild.id, roles).await? {
+            members.entry(role).or_default().extend(users);
+        }
+    }
+    Ok(members)
+}
+
+/// The roles of the member who used an interaction. Outside a guild, e.g. in a DM,
+/// their roles in the configured `tasks_guild`, or in every guild the bot is in when that's unset.
+/// Someone who left keeps only their direct assignments.
+pub async fn user_roles(
+    http: &Http,
+    config: &Config,
+    member: Option<&Member>,
+    user: UserId,
+) -> TypedResult<Vec<RoleId>> {
+    if let Some(member) = member {
+        return Ok(member.roles.clone());
+    }
+
+    let guilds = match config.tasks_guild {
+        Some(guild) => vec![guild],
+        None => http
+            .get_guilds(None, None)
+            .await?
+            .into_iter()
+            .map(|g| g.id)
+            .collect(),
+    };
+    let mut roles = vec![];
+    for guild in guilds {
+        if let Ok(member) = guild.member(http, user).await {
+            roles.extend(member.roles);
+        }
+    }
+    Ok(roles)
+}
+
+/// Everyone a task is assigned to, directly or through one of its roles,
+/// with the roles looked up like [`resolve_role_members`] does
+pub async fn task_assignees(
+    http: &Http,
+    config: &Config,
+    db: &Db,
+    guild_id: Option<GuildId>,
+    task_id: i64,
+) -> TypedResult<Vec<UserId>> {
+    let roles = db.get_assigned_roles(task_id).await?;
+    let mut assignees =
+        merge_role_members(resolve_role_members(http, config, guild_id, &roles).await?);
+    assignees.extend(db.get_assigned_users(task_id).await?);
+    assignees.sort_unstable();
+    assignees.dedup();
+    Ok(assignees)
+}
+
+/// Everyone in any of the roles, once
+pub fn merge_role_members(members: HashMap<RoleId, Vec<UserId>>) -> Vec<UserId> {
+    let mut members = members.into_values().flatten().collect::<Vec<_>>();
+    members.sort_unstable();
+    members.dedup();
+    members
 }
 
 pub fn verify_email(email: &str) -> bool {
@@ -51,5 +391,134 @@ pub fn verify_email(email: &str) -> bool {
         return false;
     }
 
-    return true;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_add_up() {
+        assert_eq!(parse_offset("2h"), Some(Duration::hours(2)));
+        assert_eq!(parse_offset("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_offset("1W 2d"), Some(Duration::days(9)));
+        assert_eq!(parse_offset("3h30m"), Some(Duration::minutes(210)));
+    }
+
+    #[test]
+    fn offsets_are_capped() {
+        assert_eq!(parse_offset("52w"), Some(MAX_OFFSET));
+        assert_eq!(parse_offset("52w 1m"), None);
+        assert_eq!(parse_offset("53w"), None);
+        assert_eq!(parse_offset("99999999999999w"), None);
+    }
+
+    #[test]
+    fn bad_offsets_are_refused() {
+        for input in ["", "0m", "2", "2x", "h", "in 2h", "-2h"] {
+            assert_eq!(parse_offset(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn offsets_round_trip() {
+        assert_eq!(format_offset(Duration::days(9)), "1w 2d");
+        assert_eq!(format_offset(Duration::minutes(210)), "3h 30m");
+        assert_eq!(format_offset(Duration::zero()), "0m");
+
+        for input in ["1w 2d", "3h 30m", "52w", "1w 6d 23h 59m"] {
+            assert_eq!(
+                parse_offset(input).map(format_offset).as_deref(),
+                Some(input)
+            );
+        }
+    }
+
+    #[test]
+    fn recurrence_shorthands() {
+        let tz = chrono_tz::Europe::Warsaw;
+        let daily = parse_recurrence("Daily", tz).unwrap();
+        assert_eq!((daily.interval, daily.unit), (1, RecurrenceUnit::Days));
+        assert!(daily.until.is_none());
+
+        let monthly = parse_recurrence("monthly", tz).unwrap();
+        assert_eq!(
+            (monthly.interval, monthly.unit),
+            (1, RecurrenceUnit::Months)
+        );
+    }
+
+    #[test]
+    fn recurrence_every() {
+        let tz = chrono_tz::Europe::Warsaw;
+        let every = parse_recurrence("every 2 weeks", tz).unwrap();
+        assert_eq!((every.interval, every.unit), (2, RecurrenceUnit::Weeks));
+
+        let every = parse_recurrence("every day", tz).unwrap();
+        assert_eq!((every.interval, every.unit), (1, RecurrenceUnit::Days));
+
+        // what the task view shows reads back the same
+        let shown = parse_recurrence(&every.to_string(), tz).unwrap();
+        assert_eq!((shown.interval, shown.unit), (1, RecurrenceUnit::Days));
+    }
+
+    #[test]
+    fn recurrence_until() {
+        let tz = chrono_tz::Europe::Warsaw;
+        let until = parse_recurrence("every month until 2030-12-24", tz).unwrap();
+        assert_eq!((until.interval, until.unit), (1, RecurrenceUnit::Months));
+        assert_eq!(
+            until.until,
+            Some(Utc.with_ymd_and_hms(2030, 12, 24, 22, 59, 0).unwrap())
+        );
+
+        assert!(parse_recurrence("every month until whenever", tz).is_none());
+    }
+
+    #[test]
+    fn bad_recurrences_are_refused() {
+        let tz = chrono_tz::Europe::Warsaw;
+        for input in [
+            "",
+            "hourly",
+            "every 0 days",
+            "every 2",
+            "every 2 days please",
+            "each day",
+        ] {
+            assert!(parse_recurrence(input, tz).is_none(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn user_ids_and_mentions() {
+        let ids = |ids: &[u64]| Some(ids.iter().copied().map(UserId::new).collect::<Vec<_>>());
+        assert_eq!(parse_user_ids("1, 2;3 4"), ids(&[1, 2, 3, 4]));
+        assert_eq!(parse_user_ids("<@5> <@!6>"), ids(&[5, 6]));
+        assert_eq!(parse_user_ids(""), ids(&[]));
+    }
+
+    #[test]
+    fn bad_user_ids_are_refused() {
+        for input in ["0", "1;0", "someone", "<@#7>", "-1"] {
+            assert_eq!(parse_user_ids(input), None, "{input:?}");
+        }
+    }
+
+    /// What `/export_tasks` writes into a CSV cell `/import_tasks` has to read back
+    #[test]
+    fn exported_lists_import_back() {
+        let users = [UserId::new(111), UserId::new(222)];
+        let cell = users
+            .iter()
+            .map(|u| u.get().to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+        assert_eq!(parse_user_ids(&cell).as_deref(), Some(&users[..]));
+
+        for offset in [Duration::minutes(15), Duration::hours(25), MAX_OFFSET] {
+            assert_eq!(parse_offset(&format_offset(offset)), Some(offset));
+        }
+    }
 }
@@ -2,12 +2,20 @@ pub mod ping;
 
 pub mod add_custom_event;
 pub mod add_task;
+pub mod calendar_preview;
 pub mod events;
+pub mod export_tasks;
 pub mod given_tasks;
+pub mod import_tasks;
 pub mod my_tasks;
 pub mod remind_events;
+pub mod stats;
 pub mod summaries;
+pub mod task_details;
+pub mod tasks;
+pub mod timezone;
 
+pub mod dates;
 pub mod misc;
 
 pub use ping::Ping;
@@ -1,16 +1,26 @@
-use modal_macro::{interactive_msg, Selection, SelectionState};
-use serenity::{all::CreateCommand, async_trait};
+use chrono::{DateTime, Utc};
+use modal_macro::{interactive_msg, modal, Selection, SelectionState};
+use serenity::{
+    all::{CreateCommand, CreateInteractionResponseMessage, UserId},
+    async_trait,
+};
 
 use crate::{
     aliases::{Result, TypedResult},
-    commands::my_tasks::embed::Embed,
+    commands::{
+        misc,
+        my_tasks::embed::{Embed, RemindersEmbed},
+        task_details::{checklist, comments},
+    },
     components::{CommandCtx, EventCtx, InteractiveMessage},
-    database::Task,
-    traits::{BotCommand, Interactable, StateTrait},
+    database::{Reminder, ReminderGroup, Task, TaskStatus},
+    handler::notify::{notify_user, Notification},
+    log_warn,
+    traits::{BotCommand, Interactable, InteractiveMessageTrait, IntoResponse, StateTrait},
 };
 
 #[derive(Selection, Clone, Debug)]
-enum ReminderWhen {
+pub enum ReminderWhen {
     #[select_value("1 Day Before")]
     OneDay,
     #[select_value("2 Days Before")]
@@ -32,29 +42,45 @@ impl From<&ReminderWhen> for chrono::Duration {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, SelectionState)]
 pub struct State {
     pub page: usize,
     pub max_page: usize,
     pub tasks: Vec<Task>,
+    #[selection_state]
+    pub status: Vec<TaskStatus>,
 }
 
 #[async_trait]
 impl StateTrait for State {
     async fn init(ctx: &CommandCtx) -> TypedResult<Self> {
-        let mut tasks = ctx.db.get_user_tasks(ctx.interaction.user.id).await?;
-        tasks.sort_unstable_by(|a, b| a.deadline.cmp(&b.deadline));
+        let roles = misc::user_roles(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.interaction.member.as_deref(),
+            ctx.interaction.user.id,
+        )
+        .await?;
+        let mut tasks = ctx
+            .db
+            .get_user_tasks(ctx.interaction.user.id, &roles)
+            .await?;
+        tasks.sort_unstable_by_key(|t| t.deadline);
         Ok(Self {
             page: 0,
             max_page: tasks.len(),
             tasks,
+            status: vec![],
         })
     }
 }
 
 #[derive(Clone, SelectionState)]
-struct ReminderState {
+pub struct ReminderState {
     pub task_id: i64,
+    pub title: String,
+    pub deadline: DateTime<Utc>,
+    pub reminders: Vec<Reminder>,
     #[selection_state]
     pub when_selection: Vec<ReminderWhen>,
 }
@@ -64,6 +90,9 @@ impl StateTrait for ReminderState {
     async fn init(_ctx: &CommandCtx) -> TypedResult<Self> {
         Ok(Self {
             task_id: 0,
+            title: String::new(),
+            deadline: Utc::now(),
+            reminders: vec![],
             when_selection: vec![],
         })
     }
@@ -71,6 +100,14 @@ impl StateTrait for ReminderState {
 
 pub struct MyTasksCommand;
 
+modal! {
+    <CustomReminderModal title="Custom Reminder" duration=600>
+        <row>
+            <input id="offset" style="short" max_len=50 placeholder="e.g. 2h, 30m, 1w 2d">"How long before the deadline"</input>
+        </row>
+    </CustomReminderModal>
+}
+
 interactive_msg! {
     <MyTasksMsg handler=Handler state=State ephemeral=true>
         <embed>Embed</embed>
@@ -78,20 +115,38 @@ interactive_msg! {
             <button id="prev">"<"</button>
             <button id="add_reminder" style="secondary">"🔔"</button>
             <button id="check_completed" style="success">"✅"</button>
+            <button id="checklist" style="secondary">"📋"</button>
             <button id="next">">"</button>
         </row>
+        <row>
+            <selection id="status" style=String options=TaskStatus placeholder="Change status"></selection>
+        </row>
+        <row>
+            <button id="add_comment" style="secondary">"💬"</button>
+            <button id="comments" style="secondary">"Comments"</button>
+        </row>
     </MyTasksMsg>
 }
 
 interactive_msg! {
-    <AddReminderMsg handler=ReminderHandler state=ReminderState ephemeral=true>
+    <RemindersMsg handler=ReminderHandler state=ReminderState ephemeral=true>
+        <embed>RemindersEmbed</embed>
         <row>
-            <selection id="when_selection" style=String options=ReminderWhen max_values=4></selection>
+            <selection id="when_selection" style=String options=ReminderWhen placeholder="Pick reminders to add" max_values=4></selection>
         </row>
         <row>
-            <button id="submit">"Ok"</button>
+            <button id="submit">"Add"</button>
+            <button id="custom" style="secondary">"Custom"</button>
+            <button id="done">"Done"</button>
         </row>
-    </AddReminderMsg>
+        <row>
+            <button id="delete_1" style="secondary">"🗑️ 1"</button>
+            <button id="delete_2" style="secondary">"🗑️ 2"</button>
+            <button id="delete_3" style="secondary">"🗑️ 3"</button>
+            <button id="delete_4" style="secondary">"🗑️ 4"</button>
+            <button id="delete_5" style="secondary">"🗑️ 5"</button>
+        </row>
+    </RemindersMsg>
 }
 
 interactive_msg! {
@@ -102,26 +157,170 @@ interactive_msg! {
 
 impl EmptyHanderTrait for EmptyHander {}
 
+/// The reminders message as it looks with `state`, for updating it from a submitted modal
+struct RemindersView(ReminderState);
+
+impl IntoResponse for RemindersView {
+    fn into_response(&self) -> CreateInteractionResponseMessage {
+        RemindersMsg::<ReminderHandler>::into_msg().embed(RemindersEmbed::create(&self.0))
+    }
+}
+
+/// The offsets that aren't set yet, or why they can't be
+fn new_offsets(
+    state: &ReminderState,
+    offsets: Vec<chrono::Duration>,
+) -> std::result::Result<Vec<chrono::Duration>, String> {
+    let mut new = offsets
+        .into_iter()
+        .filter(|o| state.reminders.iter().all(|r| r.when != *o))
+        .collect::<Vec<_>>();
+    new.sort_unstable();
+    new.dedup();
+
+    if new.is_empty() {
+        return Err("You already have that reminder!".to_owned());
+    }
+    if state.reminders.len() + new.len() > misc::MAX_REMINDERS {
+        return Err(format!(
+            "A task can have at most {} reminders, delete some first!",
+            misc::MAX_REMINDERS
+        ));
+    }
+    Ok(new)
+}
+
+async fn delete_reminder(ctx: &mut EventCtx<'_>, n: usize) -> Result {
+    let mut state = ctx.msg.clone_state::<ReminderState>().await.unwrap();
+    if n >= state.reminders.len() {
+        return ctx.acknowlage().await;
+    }
+
+    let reminder = state.reminders.remove(n);
+    ctx.db
+        .delete_reminder(reminder.id, ctx.interaction.user.id)
+        .await?;
+    ctx.msg.write_state(state).await;
+    ctx.update_msg::<RemindersMsg<ReminderHandler>>().await
+}
+
 #[async_trait]
 impl ReminderHandlerTrait for ReminderHandler {
     async fn handle_submit(ctx: &mut EventCtx) -> Result {
-        let state = ctx.msg.clone_state::<ReminderState>().await.unwrap();
-        ctx.db
-            .add_reminder(
-                state.task_id,
-                ctx.interaction.user.id,
-                state
-                    .when_selection
-                    .iter()
-                    .map(|s| s.into())
-                    .collect::<Vec<chrono::Duration>>(),
-            )
+        let mut state = ctx.msg.clone_state::<ReminderState>().await.unwrap();
+        if state.when_selection.is_empty() {
+            return ctx.acknowlage().await;
+        }
+
+        let offsets = match new_offsets(
+            &state,
+            state.when_selection.iter().map(|s| s.into()).collect(),
+        ) {
+            Err(e) => return ctx.respond(e, true).await,
+            Ok(o) => o,
+        };
+        state.reminders = ctx
+            .db
+            .add_reminder(state.task_id, ctx.interaction.user.id, offsets)
             .await?;
-        crate::log!("{:?}", state.when_selection);
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<RemindersMsg<ReminderHandler>>().await
+    }
+
+    async fn handle_custom(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<ReminderState>().await.unwrap();
+
+        let result = ctx.modal::<CustomReminderModal>().await?;
+        let offset = match misc::parse_offset(&result.offset) {
+            None => {
+                return result
+                    .respond(
+                        format!(
+                            "Couldn't read \"{}\", write it like 2h, 30m or 1w 2d, up to 52w!",
+                            result.offset.trim()
+                        ),
+                        true,
+                    )
+                    .await
+            }
+            Some(o) => o,
+        };
+        let offsets = match new_offsets(&state, vec![offset]) {
+            Err(e) => return result.respond(e, true).await,
+            Ok(o) => o,
+        };
+
+        state.reminders = ctx
+            .db
+            .add_reminder(state.task_id, ctx.interaction.user.id, offsets)
+            .await?;
+        ctx.msg.write_state(state.clone()).await;
+        result.edit(RemindersView(state)).await
+    }
+
+    async fn handle_done(ctx: &mut EventCtx) -> Result {
         ctx.update_msg::<EmptyMsg<EmptyHander>>().await?;
         ctx.msg.stop();
         Ok(())
     }
+
+    async fn handle_delete_1(ctx: &mut EventCtx) -> Result {
+        delete_reminder(ctx, 0).await
+    }
+
+    async fn handle_delete_2(ctx: &mut EventCtx) -> Result {
+        delete_reminder(ctx, 1).await
+    }
+
+    async fn handle_delete_3(ctx: &mut EventCtx) -> Result {
+        delete_reminder(ctx, 2).await
+    }
+
+    async fn handle_delete_4(ctx: &mut EventCtx) -> Result {
+        delete_reminder(ctx, 3).await
+    }
+
+    async fn handle_delete_5(ctx: &mut EventCtx) -> Result {
+        delete_reminder(ctx, 4).await
+    }
+}
+
+/// Current members of the roles the task is assigned to, None when Discord couldn't tell
+async fn task_role_members(ctx: &EventCtx<'_>, task_id: i64) -> TypedResult<Option<Vec<UserId>>> {
+    let roles = ctx.db.get_assigned_roles(task_id).await?;
+    match misc::resolve_role_members(
+        &ctx.discord_ctx.http,
+        ctx.config,
+        ctx.interaction.guild_id,
+        &roles,
+    )
+    .await
+    {
+        Ok(members) => Ok(Some(misc::merge_role_members(members))),
+        Err(e) => {
+            log_warn!("Failed to look up the role members of task {task_id}! {e}");
+            Ok(None)
+        }
+    }
+}
+
+async fn request_review(ctx: &EventCtx<'_>, task: &Task) -> Result {
+    notify_user(
+        &ctx.discord_ctx.http,
+        ctx.config,
+        ctx.db,
+        task.given_by,
+        ReminderGroup::Tasks,
+        &Notification {
+            title: format!("Ready for review: {}", task.title),
+            description: format!(
+                "<@{}> moved the task to review, approve it or send it back in /given_tasks",
+                ctx.interaction.user.id
+            ),
+        },
+    )
+    .await?;
+    Ok(())
 }
 
 #[async_trait]
@@ -163,32 +362,195 @@ impl HandlerTrait for Handler {
             Some(t) => t,
         };
 
-        task.completed = !task.completed;
-        ctx.db.toggle_task_completion(task.id).await?;
+        if task.is_blocked() {
+            return ctx
+                .respond("This task is still blocked by unfinished tasks!", true)
+                .await;
+        }
+        if task.status == TaskStatus::Done {
+            return ctx.respond("This task was already approved!", true).await;
+        }
+
+        let role_members = task_role_members(ctx, task.id).await?;
+
+        let user_id = ctx.interaction.user.id;
+        task.completed = ctx
+            .db
+            .toggle_task_completion(task.id, user_id, role_members.as_deref())
+            .await?;
+        match task.completed_by.iter().position(|u| *u == user_id) {
+            Some(i) => {
+                task.completed_by.remove(i);
+            }
+            None => task.completed_by.push(user_id),
+        }
+
+        if task.completed && matches!(task.status, TaskStatus::Todo | TaskStatus::InProgress) {
+            task.status = TaskStatus::InReview;
+            ctx.db
+                .set_task_status(
+                    task.id,
+                    task.status,
+                    role_members.as_deref().unwrap_or_default(),
+                    user_id,
+                )
+                .await?;
+            request_review(ctx, task).await?;
+        }
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<MyTasksMsg<Handler>>().await
+    }
+
+    async fn handle_status(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        let Some(status) = state.status.first().copied() else {
+            return ctx.acknowlage().await;
+        };
+        let task = match state.tasks.get_mut(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t,
+        };
+
+        if task.is_blocked() && status != TaskStatus::Todo {
+            return ctx
+                .respond("This task is still blocked by unfinished tasks!", true)
+                .await;
+        }
+
+        if !task.status.can_move_to(status, false) {
+            return ctx
+                .respond(
+                    format!(
+                        "You can't move this task from {} to {}!",
+                        task.status, status
+                    ),
+                    true,
+                )
+                .await;
+        }
+
+        let role_members = task_role_members(ctx, task.id).await?;
+        task.status = status;
+        ctx.db
+            .set_task_status(
+                task.id,
+                status,
+                role_members.as_deref().unwrap_or_default(),
+                ctx.interaction.user.id,
+            )
+            .await?;
+        if matches!(status, TaskStatus::Todo | TaskStatus::InProgress) {
+            task.completed = false;
+            task.completed_by.clear();
+        }
+        if status == TaskStatus::InReview {
+            request_review(ctx, task).await?;
+        }
+
         ctx.msg.write_state(state).await;
         ctx.update_msg::<MyTasksMsg<Handler>>().await
     }
 
+    async fn handle_add_comment(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        match state.tasks.get(state.page) {
+            None => ctx.acknowlage().await,
+            Some(task) => comments::add(ctx, task).await,
+        }
+    }
+
+    async fn handle_comments(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        match state.tasks.get(state.page) {
+            None => ctx.acknowlage().await,
+            Some(task) => comments::open(ctx, task).await,
+        }
+    }
+
+    async fn handle_checklist(ctx: &mut EventCtx) -> Result {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        let task = match state.tasks.get(state.page) {
+            None => return ctx.acknowlage().await,
+            Some(t) => t,
+        };
+        let task_id = task.id;
+
+        let checklist = checklist::open(ctx, task, None).await?;
+
+        // the task view shows the progress on its next update
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if let Some(task) = state.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.checklist = checklist;
+        }
+        ctx.msg.write_state(state).await;
+        Ok(())
+    }
+
     async fn handle_add_reminder(ctx: &mut EventCtx) -> Result {
         let state = ctx.msg.clone_state::<State>().await.unwrap();
         let task = match state.tasks.get(state.page) {
             None => return ctx.acknowlage().await,
             Some(t) => t,
         };
+        let task_id = task.id;
 
         let mut msg =
-            InteractiveMessage::from_event::<AddReminderMsg<ReminderHandler>, ReminderState>(
+            InteractiveMessage::from_event::<RemindersMsg<ReminderHandler>, ReminderState>(
                 ctx,
                 ReminderState {
-                    task_id: task.id,
+                    task_id,
+                    title: task.title.clone(),
+                    deadline: task.deadline,
+                    reminders: task.reminders.clone(),
                     when_selection: vec![],
                 },
             )
             .await?;
-        msg.handle_events_from_event(ctx).await
+        msg.handle_events_from_event(ctx).await?;
+
+        // the task view shows them on its next update
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if let Some(task) = state.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.reminders = ctx
+                .db
+                .get_reminders(task_id, ctx.interaction.user.id)
+                .await?;
+        }
+        ctx.msg.write_state(state).await;
+        Ok(())
     }
 }
 
+/// Opens the usual view on a single task assigned to the user, e.g. one picked from search results
+pub async fn open_task(ctx: &mut EventCtx<'_>, task_id: i64) -> Result {
+    let roles = misc::user_roles(
+        &ctx.discord_ctx.http,
+        ctx.config,
+        ctx.interaction.member.as_ref(),
+        ctx.interaction.user.id,
+    )
+    .await?;
+    let task = match ctx
+        .db
+        .get_user_tasks(ctx.interaction.user.id, &roles)
+        .await?
+        .into_iter()
+        .find(|t| t.id == task_id)
+    {
+        None => return ctx.respond("That task isn't assigned to you!", true).await,
+        Some(t) => t,
+    };
+
+    let state = State {
+        page: 0,
+        max_page: 1,
+        tasks: vec![task],
+        status: vec![],
+    };
+    let mut msg = InteractiveMessage::from_event::<MyTasksMsg<Handler>, State>(ctx, state).await?;
+    msg.handle_events_from_event(ctx).await
+}
+
 #[async_trait]
 impl BotCommand for MyTasksCommand {
     async fn run(&self, ctx: &CommandCtx) -> Result {
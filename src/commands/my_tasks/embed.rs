@@ -1,10 +1,13 @@
 use serenity::{
-    all::{CreateEmbed, CreateEmbedFooter},
+    all::{CreateEmbed, CreateEmbedFooter, UserId},
     async_trait,
 };
 
 use crate::{
-    commands::my_tasks::command::State,
+    commands::{
+        misc,
+        my_tasks::command::{ReminderState, State},
+    },
     components::{CommandCtx, EventCtx},
     database::Task,
     traits::{into_embed::IntoEmbedInteractive, IntoEmbed},
@@ -19,61 +22,132 @@ impl IntoEmbed for Embed {
 }
 
 impl Embed {
-    fn format_task(embed: CreateEmbed, task: &Task) -> CreateEmbed {
+    fn format_task(embed: CreateEmbed, task: &Task, user_id: UserId) -> CreateEmbed {
         let reminder_string = if task.reminders.is_empty() {
             "No reminders".to_owned()
         } else {
             task.reminders
                 .iter()
-                .map(|reminder| format!("{} Days Before\n", reminder.when.num_days()))
+                .map(|reminder| format!("{}\n", reminder))
                 .collect::<String>()
         };
 
-        embed.title(task.title.clone()).fields(vec![
-            ("Description", task.description.clone(), false),
-            (
-                "Deadline",
-                format!("<t:{}:D>", task.deadline.timestamp()),
-                true,
-            ),
-            (
-                "Completed",
-                if task.completed {
-                    "Yes".to_owned()
-                } else {
-                    "No".to_owned()
-                },
-                true,
-            ),
-            ("Reminders", reminder_string, false),
-        ])
+        let embed = if task.is_overdue() {
+            embed.color(serenity::model::colour::Color::RED)
+        } else {
+            embed
+        };
+
+        embed
+            .title(task.title.clone())
+            .fields(vec![
+                ("Description", task.description.clone(), false),
+                ("Deadline", misc::format_deadline(task), true),
+                (
+                    "Completed",
+                    match (task.completed_by.contains(&user_id), task.completed) {
+                        (true, true) => "Yes, by everyone".to_owned(),
+                        (true, false) => "Yes".to_owned(),
+                        (false, _) => "No".to_owned(),
+                    },
+                    true,
+                ),
+                ("Status", task.status.to_string(), true),
+                ("Reminders", reminder_string, false),
+            ])
+            .fields((!task.blocked_by.is_empty()).then(|| {
+                (
+                    "Blocked by",
+                    task.blocked_by
+                        .iter()
+                        .map(|b| format!("{}\n", b))
+                        .collect::<String>(),
+                    false,
+                )
+            }))
+            .fields(
+                task.checklist_progress()
+                    .map(|progress| ("Checklist", progress, true)),
+            )
+            .fields(
+                task.recurrence
+                    .iter()
+                    .map(|r| ("Repeats", r.to_string(), false)),
+            )
     }
 
-    fn create(state: &State) -> CreateEmbed {
+    fn create(state: &State, user_id: UserId) -> CreateEmbed {
         let embed = Self::into_embed();
 
         let embed = match state.tasks.get(state.page) {
             None => return embed.field("Task", "No Tasks", false),
-            Some(t) => Self::format_task(embed, t),
+            Some(t) => Self::format_task(embed, t, user_id),
         };
 
         embed.footer(CreateEmbedFooter::new(format!(
-            "{}/{}",
+            "{}/{} • Task #{}",
             state.page + 1,
-            state.max_page
+            state.max_page,
+            state.tasks[state.page].id
         )))
     }
 }
 
 #[async_trait]
 impl IntoEmbedInteractive for Embed {
-    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+    async fn from_command(ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
         let state = state.clone::<State>().await.unwrap();
-        Self::create(&state)
+        Self::create(&state, ctx.interaction.user.id)
     }
 
     async fn from_event(ctx: &EventCtx) -> CreateEmbed {
         let state = ctx.msg.clone_state::<State>().await.unwrap();
+        Self::create(&state, ctx.interaction.user.id)
+    }
+}
+
+pub struct RemindersEmbed;
+
+impl RemindersEmbed {
+    pub fn create(state: &ReminderState) -> CreateEmbed {
+        let reminders = if state.reminders.is_empty() {
+            "No reminders yet".to_owned()
+        } else {
+            state
+                .reminders
+                .iter()
+                .enumerate()
+                .map(|(i, reminder)| {
+                    match state.deadline.checked_sub_signed(reminder.when) {
+                        Some(at) => {
+                            format!("**{}.** {} (<t:{}:f>)\n", i + 1, reminder, at.timestamp())
+                        }
+                        None => format!("**{}.** {}\n", i + 1, reminder),
+                    }
+                })
+                .collect::<String>()
+        };
+
+        Embed::into_embed()
+            .title(format!("Reminders: {}", state.title))
+            .description(reminders)
+            .footer(CreateEmbedFooter::new(format!(
+                "{}/{} reminders",
+                state.reminders.len(),
+                misc::MAX_REMINDERS
+            )))
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for RemindersEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<ReminderState>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<ReminderState>().await.unwrap();
         Self::create(&state)
     }
 }
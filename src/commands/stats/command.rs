@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use modal_macro::command;
+use serde::Serialize;
+use serenity::{
+    all::{
+        CreateAttachment, CreateCommand, CreateEmbed, CreateEmbedFooter, EditInteractionResponse,
+        UserId,
+    },
+    async_trait,
+};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    commands::misc,
+    components::CommandCtx,
+    database::{Assignment, TaskFilter},
+    traits::{BotCommand, Interactable},
+};
+
+command! {
+    <Stats>
+        <description>"Who finished how many tasks, and how fast"</description>
+        <option required=false>
+            <name>"from"</name>
+            <description>"Tasks due on or after this day, e.g. 01-10-26"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"to"</name>
+            <description>"Tasks due by this date, e.g. 31-12-26"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"csv"</name>
+            <description>"Also attach every member's numbers as a CSV file"</description>
+            <option_type>Boolean</option_type>
+        </option>
+    </Stats>
+}
+
+pub struct StatsCommand;
+
+/// Members listed in the embed, everyone is in the CSV
+const LEADERBOARD_SIZE: usize = 15;
+
+#[derive(Default)]
+struct MemberStats {
+    assigned: usize,
+    on_time: usize,
+    late: usize,
+    overdue: usize,
+    /// Summed time from being assigned to finishing, for the average
+    lead_time: Duration,
+    /// Finished tasks counted in `lead_time`, the ones without an assignment time aren't
+    timed: usize,
+}
+
+impl MemberStats {
+    fn add(&mut self, assignment: &Assignment, now: DateTime<Utc>) {
+        self.assigned += 1;
+        match assignment.completed {
+            Some(done) => {
+                if done <= assignment.deadline {
+                    self.on_time += 1;
+                } else {
+                    self.late += 1;
+                }
+                if let Some(assigned) = assignment.assigned {
+                    self.lead_time += (done - assigned).max(Duration::zero());
+                    self.timed += 1;
+                }
+            }
+            None if assignment.is_overdue(now) => self.overdue += 1,
+            None => (),
+        }
+    }
+
+    fn completed(&self) -> usize {
+        self.on_time + self.late
+    }
+
+    fn average_lead_time(&self) -> Option<Duration> {
+        let timed = i32::try_from(self.timed).ok().filter(|c| *c > 0)?;
+        Some(self.lead_time / timed)
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} assigned • {} on time • {} late • {} overdue • avg {}",
+            self.assigned,
+            self.on_time,
+            self.late,
+            self.overdue,
+            self.average_lead_time()
+                .map(misc::format_offset)
+                .unwrap_or_else(|| "-".to_owned())
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct CsvRow {
+    user: u64,
+    name: String,
+    assigned: usize,
+    on_time: usize,
+    late: usize,
+    overdue: usize,
+    average_lead_time_hours: Option<f64>,
+}
+
+/// Members ordered by finished tasks, the ones finished on time break ties
+fn leaderboard(assignments: &[Assignment]) -> Vec<(UserId, MemberStats)> {
+    let now = Utc::now();
+    let mut members: HashMap<UserId, MemberStats> = HashMap::new();
+    for assignment in assignments {
+        members
+            .entry(assignment.user_id)
+            .or_default()
+            .add(assignment, now);
+    }
+
+    let mut members = members.into_iter().collect::<Vec<_>>();
+    members.sort_by(|(a_id, a), (b_id, b)| {
+        b.completed()
+            .cmp(&a.completed())
+            .then(b.on_time.cmp(&a.on_time))
+            .then(a.overdue.cmp(&b.overdue))
+            .then(a_id.cmp(b_id))
+    });
+    members
+}
+
+fn totals(assignments: &[Assignment]) -> MemberStats {
+    let now = Utc::now();
+    let mut totals = MemberStats::default();
+    for assignment in assignments {
+        totals.add(assignment, now);
+    }
+    totals
+}
+
+fn period(from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> String {
+    match (from, to) {
+        (None, None) => "All time".to_owned(),
+        (Some(from), None) => format!("Due since <t:{}:d>", from.timestamp()),
+        (None, Some(to)) => format!("Due by <t:{}:d>", to.timestamp()),
+        (Some(from), Some(to)) => {
+            format!("Due <t:{}:d> – <t:{}:d>", from.timestamp(), to.timestamp())
+        }
+    }
+}
+
+fn embed(
+    assignments: &[Assignment],
+    members: &[(UserId, MemberStats)],
+    period: String,
+) -> CreateEmbed {
+    let mut tasks = assignments.iter().map(|a| a.task_id).collect::<Vec<_>>();
+    tasks.sort_unstable();
+    tasks.dedup();
+
+    let board = if members.is_empty() {
+        "Nobody had tasks due then".to_owned()
+    } else {
+        members
+            .iter()
+            .take(LEADERBOARD_SIZE)
+            .enumerate()
+            .map(|(i, (user, stats))| format!("**{}.** <@{}>\n{}\n", i + 1, user, stats.summary()))
+            .collect::<String>()
+    };
+
+    // the leaderboard goes in the description, a field would cut it at 1024 characters
+    CreateEmbed::new()
+        .color(serenity::model::colour::Color::MEIBE_PINK)
+        .title("Task Statistics")
+        .description(format!("{}\n\n**Leaderboard**\n{}", period, board))
+        .field(
+            "Club",
+            format!(
+                "{} tasks, {} members\n{}",
+                tasks.len(),
+                members.len(),
+                totals(assignments).summary()
+            ),
+            false,
+        )
+        .footer(CreateEmbedFooter::new(
+            "Lead time is from being assigned to finishing",
+        ))
+}
+
+async fn to_csv(ctx: &CommandCtx<'_>, members: &[(UserId, MemberStats)]) -> TypedResult<Vec<u8>> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for (user, stats) in members {
+        let name = match user.to_user(ctx.discord_ctx).await {
+            Ok(u) => u.global_name.unwrap_or(u.name),
+            Err(_) => user.to_string(),
+        };
+        writer.serialize(CsvRow {
+            user: user.get(),
+            name,
+            assigned: stats.assigned,
+            on_time: stats.on_time,
+            late: stats.late,
+            overdue: stats.overdue,
+            average_lead_time_hours: stats
+                .average_lead_time()
+                .map(|d| d.num_minutes() as f64 / 60.0),
+        })?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()).into())
+}
+
+#[async_trait]
+impl BotCommand for StatsCommand {
+    async fn run(&self, ctx: &CommandCtx) -> Result {
+        let options = Stats::options(ctx);
+        let filter =
+            match misc::task_filter(ctx, TaskFilter::default(), options.from, options.to).await? {
+                Ok(f) => f,
+                Err(e) => return ctx.respond(e, true).await,
+            };
+
+        // going through the members of every assigned role takes a while
+        ctx.defer(true).await?;
+        let roles = ctx.db.get_roles_with_tasks(filter.from, filter.to).await?;
+        let role_members = match ctx.interaction.guild_id {
+            Some(guild_id) => misc::role_members(&ctx.discord_ctx.http, guild_id, &roles).await?,
+            None => HashMap::new(),
+        };
+        let assignments = ctx
+            .db
+            .get_assignments(filter.from, filter.to, &role_members)
+            .await?;
+
+        let members = leaderboard(&assignments);
+        let mut response = EditInteractionResponse::new().embed(embed(
+            &assignments,
+            &members,
+            period(filter.from, filter.to),
+        ));
+        if options.csv == Some(true) {
+            response = response.new_attachment(CreateAttachment::bytes(
+                to_csv(ctx, &members).await?,
+                "stats.csv",
+            ));
+        }
+
+        ctx.finish_deferred(response).await
+    }
+
+    fn register(&self, create: CreateCommand) -> CreateCommand {
+        Stats::new(create)
+    }
+}
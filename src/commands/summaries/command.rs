@@ -33,8 +33,8 @@ interactive_msg! {
         <row>
             <button id="prev">"<"</button>
             <button id="add" style="secondary">"+"</button>
-            <button id="delete" style="danger">"🗑️"</button>
-            <button id="send" style="success">"✉️"</button>
+            <button id="delete" style="danger" capability=Board>"🗑️"</button>
+            <button id="send" style="success" capability=Board>"✉️"</button>
             <button id="next">">"</button>
         </row>
     </SummariesMsg>
@@ -44,7 +44,7 @@ interactive_msg! {
     <ConfirmMsg handler=ConfirmHandler ephemeral=true>
         <text>"Are you sure?"</text>
         <row>
-            <button id="confirm">"Confirm"</button>
+            <button id="confirm" capability=Board>"Confirm"</button>
         </row>
     </ConfirmMsg>
 }
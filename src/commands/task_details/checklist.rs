@@ -0,0 +1,193 @@
+use chrono::Utc;
+use modal_macro::{interactive_msg, modal};
+use serenity::{
+    all::{CreateInteractionResponseMessage, UserId},
+    async_trait,
+};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    commands::{misc, task_details::embed::ChecklistEmbed},
+    components::{CommandCtx, EventCtx, InteractiveMessage},
+    database::{ChecklistItem, Task},
+    traits::{Interactable, InteractiveMessageTrait, IntoResponse, StateTrait},
+};
+
+#[derive(Clone)]
+pub struct ChecklistState {
+    pub task: Task,
+    pub item: usize,
+    /// Who may add and remove items, anyone when `None`
+    pub owner: Option<UserId>,
+}
+
+#[async_trait]
+impl StateTrait for ChecklistState {
+    async fn init(ctx: &CommandCtx) -> TypedResult<Self> {
+        Ok(Self {
+            task: Task::new("", "", Utc::now(), ctx.interaction.user.id),
+            item: 0,
+            owner: None,
+        })
+    }
+
+    fn task_owner(&self) -> Option<UserId> {
+        self.owner
+    }
+}
+
+modal! {
+    <AddItemsModal title="Add Checklist Items" duration=600>
+        <row>
+            <input id="items" style="paragraph" placeholder="One item per line">"Items"</input>
+        </row>
+    </AddItemsModal>
+}
+
+interactive_msg! {
+    <ChecklistMsg handler=ChecklistHandler state=ChecklistState ephemeral=true>
+        <embed>ChecklistEmbed</embed>
+        <row>
+            <button id="prev_item">"▲"</button>
+            <button id="tick" style="success">"☑️"</button>
+            <button id="next_item">"▼"</button>
+        </row>
+        <row>
+            <button id="add_items" capability=TaskOwner style="secondary">"+ Add Items"</button>
+            <button id="remove_item" capability=TaskOwner style="danger">"🗑️"</button>
+            <button id="done">"Done"</button>
+        </row>
+    </ChecklistMsg>
+}
+
+interactive_msg! {
+    <EmptyMsg handler=EmptyHandler ephemeral=true>
+        <text>"Done!"</text>
+    </EmptyMsg>
+}
+
+impl EmptyHandlerTrait for EmptyHandler {}
+
+/// The checklist message as it looks with `state`, for updating it from a submitted modal
+struct ChecklistView(ChecklistState);
+
+impl IntoResponse for ChecklistView {
+    fn into_response(&self) -> CreateInteractionResponseMessage {
+        ChecklistMsg::<ChecklistHandler>::into_msg().embed(ChecklistEmbed::create(&self.0))
+    }
+}
+
+/// Opens the checklist of `task` in its own message, returns the items as they were left
+pub async fn open(
+    ctx: &mut EventCtx<'_>,
+    task: &Task,
+    owner: Option<UserId>,
+) -> TypedResult<Vec<ChecklistItem>> {
+    let mut msg = InteractiveMessage::from_event::<ChecklistMsg<ChecklistHandler>, ChecklistState>(
+        ctx,
+        ChecklistState {
+            task: task.clone(),
+            item: 0,
+            owner,
+        },
+    )
+    .await?;
+    msg.handle_events_from_event(ctx).await?;
+
+    Ok(msg
+        .clone_state::<ChecklistState>()
+        .await
+        .map(|state| state.task.checklist)
+        .unwrap_or_else(|| task.checklist.clone()))
+}
+
+async fn toggle(ctx: &EventCtx<'_>, item: &mut ChecklistItem, user: UserId) -> Result {
+    item.done_by = match item.done_by {
+        Some(_) => None,
+        None => Some(user),
+    };
+    ctx.db
+        .set_checklist_item_done(item.id, item.done_by.is_some(), user)
+        .await
+}
+
+#[async_trait]
+impl ChecklistHandlerTrait for ChecklistHandler {
+    async fn handle_prev_item(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<ChecklistState>().await.unwrap();
+        let len = state.task.checklist.len();
+        if len == 0 {
+            return ctx.acknowlage().await;
+        }
+
+        state.item = (state.item + len - 1) % len;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<ChecklistMsg<ChecklistHandler>>().await
+    }
+
+    async fn handle_next_item(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<ChecklistState>().await.unwrap();
+        let len = state.task.checklist.len();
+        if len == 0 {
+            return ctx.acknowlage().await;
+        }
+
+        state.item = (state.item + 1) % len;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<ChecklistMsg<ChecklistHandler>>().await
+    }
+
+    async fn handle_tick(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<ChecklistState>().await.unwrap();
+        let item = match state.task.checklist.get_mut(state.item) {
+            None => return ctx.acknowlage().await,
+            Some(i) => i,
+        };
+
+        toggle(ctx, item, ctx.interaction.user.id).await?;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<ChecklistMsg<ChecklistHandler>>().await
+    }
+
+    async fn handle_add_items(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<ChecklistState>().await.unwrap();
+
+        let result = ctx.modal::<AddItemsModal>().await?;
+        let items = misc::parse_checklist_items(&result.items);
+        if items.is_empty() {
+            return result.respond("Nothing to add!", true).await;
+        }
+
+        let added = ctx
+            .db
+            .add_checklist_items(state.task.id, &items, ctx.interaction.user.id)
+            .await?;
+        state.task.checklist.extend(added);
+        ctx.msg.write_state(state.clone()).await;
+        result.edit(ChecklistView(state)).await
+    }
+
+    async fn handle_remove_item(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<ChecklistState>().await.unwrap();
+        if state.item >= state.task.checklist.len() {
+            return ctx.acknowlage().await;
+        }
+
+        let item = state.task.checklist.remove(state.item);
+        ctx.db
+            .delete_checklist_item(item.id, ctx.interaction.user.id)
+            .await?;
+        if state.item == state.task.checklist.len() && state.item != 0 {
+            state.item -= 1;
+        }
+
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<ChecklistMsg<ChecklistHandler>>().await
+    }
+
+    async fn handle_done(ctx: &mut EventCtx) -> Result {
+        ctx.update_msg::<EmptyMsg<EmptyHandler>>().await?;
+        ctx.msg.stop();
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::Utc;
+use modal_macro::{interactive_msg, modal};
+use serenity::{all::CreateInteractionResponseMessage, async_trait};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    commands::{misc, task_details::embed::CommentsEmbed},
+    components::{CommandCtx, EventCtx, InteractiveMessage},
+    database::{Comment, ReminderGroup, Task},
+    handler::notify::{notify_user, Notification},
+    traits::{Interactable, InteractiveMessageTrait, IntoResponse, StateTrait},
+};
+
+#[derive(Clone)]
+pub struct CommentsState {
+    pub task: Task,
+    pub comments: Vec<Comment>,
+    pub page: usize,
+}
+
+#[async_trait]
+impl StateTrait for CommentsState {
+    async fn init(ctx: &CommandCtx) -> TypedResult<Self> {
+        Ok(Self {
+            task: Task::new("", "", Utc::now(), ctx.interaction.user.id),
+            comments: vec![],
+            page: 0,
+        })
+    }
+}
+
+modal! {
+    <CommentModal title="Comment" duration=600>
+        <row>
+            <input id="content" style="paragraph" max_len=1000>"Comment"</input>
+        </row>
+    </CommentModal>
+}
+
+interactive_msg! {
+    <CommentsMsg handler=CommentsHandler state=CommentsState ephemeral=true>
+        <embed>CommentsEmbed</embed>
+        <row>
+            <button id="prev_comments">"<"</button>
+            <button id="add_comment" style="secondary">"💬"</button>
+            <button id="done">"Done"</button>
+            <button id="next_comments">">"</button>
+        </row>
+    </CommentsMsg>
+}
+
+interactive_msg! {
+    <EmptyMsg handler=EmptyHandler ephemeral=true>
+        <text>"Done!"</text>
+    </EmptyMsg>
+}
+
+impl EmptyHandlerTrait for EmptyHandler {}
+
+/// The comments message as it looks with `state`, for updating it from a submitted modal
+struct CommentsView(CommentsState);
+
+impl IntoResponse for CommentsView {
+    fn into_response(&self) -> CreateInteractionResponseMessage {
+        CommentsMsg::<CommentsHandler>::into_msg().embed(CommentsEmbed::create(&self.0))
+    }
+}
+
+/// Lets the giver and the other assignees know about a new comment
+async fn notify_comment(ctx: &EventCtx<'_>, task: &Task, comment: &Comment) -> Result {
+    let mut recipients = misc::task_assignees(
+        &ctx.discord_ctx.http,
+        ctx.config,
+        ctx.db,
+        ctx.interaction.guild_id,
+        task.id,
+    )
+    .await?;
+    recipients.push(task.given_by);
+    recipients.sort_unstable();
+    recipients.dedup();
+
+    let notification = Notification {
+        title: format!("New comment on {}", task.title),
+        description: comment.to_string(),
+    };
+    for user in recipients.into_iter().filter(|u| *u != comment.author) {
+        notify_user(
+            &ctx.discord_ctx.http,
+            ctx.config,
+            ctx.db,
+            user,
+            ReminderGroup::Tasks,
+            &notification,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn post(ctx: &EventCtx<'_>, task: &Task, content: &str) -> TypedResult<Comment> {
+    let comment = ctx
+        .db
+        .add_comment(task.id, ctx.interaction.user.id, content.trim())
+        .await?;
+    notify_comment(ctx, task, &comment).await?;
+    Ok(comment)
+}
+
+/// Asks for a comment on `task` and posts it
+pub async fn add(ctx: &mut EventCtx<'_>, task: &Task) -> Result {
+    let result = ctx.modal::<CommentModal>().await?;
+    post(ctx, task, &result.content).await?;
+    result.respond("Comment added!", true).await
+}
+
+/// Opens the comments on `task` in their own message
+pub async fn open(ctx: &mut EventCtx<'_>, task: &Task) -> Result {
+    let comments = ctx.db.get_comments(task.id).await?;
+    let mut msg = InteractiveMessage::from_event::<CommentsMsg<CommentsHandler>, CommentsState>(
+        ctx,
+        CommentsState {
+            task: task.clone(),
+            comments,
+            page: 0,
+        },
+    )
+    .await?;
+    msg.handle_events_from_event(ctx).await
+}
+
+#[async_trait]
+impl CommentsHandlerTrait for CommentsHandler {
+    async fn handle_prev_comments(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<CommentsState>().await.unwrap();
+        if state.page == 0 {
+            return ctx.acknowlage().await;
+        }
+
+        state.page -= 1;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<CommentsMsg<CommentsHandler>>().await
+    }
+
+    async fn handle_next_comments(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<CommentsState>().await.unwrap();
+        if (state.page + 1) * misc::COMMENTS_PER_PAGE >= state.comments.len() {
+            return ctx.acknowlage().await;
+        }
+
+        state.page += 1;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<CommentsMsg<CommentsHandler>>().await
+    }
+
+    async fn handle_add_comment(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<CommentsState>().await.unwrap();
+
+        let result = ctx.modal::<CommentModal>().await?;
+        let comment = post(ctx, &state.task, &result.content).await?;
+
+        state.comments.insert(0, comment);
+        state.page = 0;
+        ctx.msg.write_state(state.clone()).await;
+        result.edit(CommentsView(state)).await
+    }
+
+    async fn handle_done(ctx: &mut EventCtx) -> Result {
+        ctx.update_msg::<EmptyMsg<EmptyHandler>>().await?;
+        ctx.msg.stop();
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use serenity::{
+    all::{CreateEmbed, CreateEmbedFooter},
+    async_trait,
+};
+
+use crate::{
+    commands::{
+        misc::{self, COMMENTS_PER_PAGE, COMMENT_PREVIEW_LEN},
+        task_details::{checklist::ChecklistState, comments::CommentsState},
+    },
+    components::{CommandCtx, EventCtx},
+    database::Comment,
+    traits::into_embed::IntoEmbedInteractive,
+};
+
+fn base_embed() -> CreateEmbed {
+    CreateEmbed::new().color(serenity::model::colour::Color::MEIBE_PINK)
+}
+
+pub struct ChecklistEmbed;
+
+impl ChecklistEmbed {
+    pub fn create(state: &ChecklistState) -> CreateEmbed {
+        let task = &state.task;
+        let items = if task.checklist.is_empty() {
+            "No items yet".to_owned()
+        } else {
+            task.checklist
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let cursor = if i == state.item { "▶ " } else { "" };
+                    format!("{}{}\n", cursor, item)
+                })
+                .collect::<String>()
+        };
+
+        base_embed()
+            .title(task.title.clone())
+            .field("Checklist", items, false)
+            .footer(CreateEmbedFooter::new(
+                task.checklist_progress().unwrap_or("0/0".to_owned()),
+            ))
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for ChecklistEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<ChecklistState>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<ChecklistState>().await.unwrap();
+        Self::create(&state)
+    }
+}
+
+pub struct CommentsEmbed;
+
+impl CommentsEmbed {
+    pub fn create(state: &CommentsState) -> CreateEmbed {
+        let embed = base_embed().title(format!("Comments: {}", state.task.title));
+        if state.comments.is_empty() {
+            return embed.description("No comments yet");
+        }
+
+        let comments = state
+            .comments
+            .iter()
+            .skip(state.page * COMMENTS_PER_PAGE)
+            .take(COMMENTS_PER_PAGE)
+            .map(|comment| {
+                let preview = Comment {
+                    content: misc::truncate(&comment.content, COMMENT_PREVIEW_LEN),
+                    ..comment.clone()
+                };
+                format!("{}\n\n", preview)
+            })
+            .collect::<String>();
+
+        embed
+            .description(comments)
+            .footer(CreateEmbedFooter::new(format!(
+                "{}/{}",
+                state.page + 1,
+                state.comments.len().div_ceil(COMMENTS_PER_PAGE)
+            )))
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for CommentsEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<CommentsState>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<CommentsState>().await.unwrap();
+        Self::create(&state)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod checklist;
+pub mod comments;
+pub mod embed;
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use modal_macro::{command, interactive_msg};
+use serenity::{
+    all::{CreateCommand, CreateEmbed, CreateInteractionResponseMessage},
+    async_trait,
+};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    commands::{
+        given_tasks, misc, my_tasks,
+        tasks::embed::{Embed, SearchEmbed},
+    },
+    components::{CommandCtx, EventCtx, InteractiveMessage},
+    database::{Task, TaskFilter},
+    permissions::Capability,
+    traits::{BotCommand, Interactable, IntoEmbed, IntoResponse, StateTrait},
+};
+
+command! {
+    <TasksSearch subcommand="search">
+        <description>"Search tasks you gave or are assigned, the board searches all of them"</description>
+        <option required=false>
+            <name>"text"</name>
+            <description>"Part of the title or description"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"assignee"</name>
+            <description>"Assigned to this member, directly or through a role"</description>
+            <option_type>User</option_type>
+        </option>
+        <option required=false>
+            <name>"giver"</name>
+            <description>"Given by this member"</description>
+            <option_type>User</option_type>
+        </option>
+        <option required=false>
+            <name>"completed"</name>
+            <description>"Completed by everyone assigned"</description>
+            <option_type>Boolean</option_type>
+        </option>
+        <option required=false>
+            <name>"from"</name>
+            <description>"Deadline on or after this day, e.g. today, 24-12-26"</description>
+            <option_type>String</option_type>
+        </option>
+        <option required=false>
+            <name>"to"</name>
+            <description>"Deadline by this date, e.g. next friday, 31-12-26 18:00"</description>
+            <option_type>String</option_type>
+        </option>
+    </TasksSearch>
+}
+
+pub struct TasksCommand;
+
+#[derive(Clone)]
+pub struct State {
+    pub page: usize,
+    pub results: Vec<Task>,
+}
+
+#[async_trait]
+impl StateTrait for State {
+    async fn init(_ctx: &CommandCtx) -> TypedResult<Self> {
+        Ok(Self {
+            page: 0,
+            results: vec![],
+        })
+    }
+}
+
+impl State {
+    pub fn max_page(&self) -> usize {
+        self.results
+            .len()
+            .div_ceil(misc::SEARCH_RESULTS_PER_PAGE)
+            .max(1)
+    }
+}
+
+interactive_msg! {
+    <SearchMsg handler=Handler state=State ephemeral=true>
+        <embed>SearchEmbed</embed>
+        <row>
+            <button id="prev">"<"</button>
+            <button id="next">">"</button>
+        </row>
+        <row>
+            <button id="open_1" style="secondary">"1"</button>
+            <button id="open_2" style="secondary">"2"</button>
+            <button id="open_3" style="secondary">"3"</button>
+            <button id="open_4" style="secondary">"4"</button>
+            <button id="open_5" style="secondary">"5"</button>
+        </row>
+    </SearchMsg>
+}
+
+/// A task the user has nothing to do with, shown without any buttons
+struct ReadOnly(CreateEmbed);
+
+impl IntoResponse for ReadOnly {
+    fn into_response(&self) -> CreateInteractionResponseMessage {
+        CreateInteractionResponseMessage::new().embed(self.0.clone())
+    }
+}
+
+/// Opens the `n`th result on the current page in the view the user would normally see it in
+async fn open(ctx: &mut EventCtx<'_>, n: usize) -> Result {
+    let state = ctx.msg.clone_state::<State>().await.unwrap();
+    let task = match state
+        .results
+        .get(state.page * misc::SEARCH_RESULTS_PER_PAGE + n)
+    {
+        None => return ctx.acknowlage().await,
+        Some(t) => t.clone(),
+    };
+
+    let user = ctx.interaction.user.id;
+    let roles = misc::user_roles(
+        &ctx.discord_ctx.http,
+        ctx.config,
+        ctx.interaction.member.as_ref(),
+        user,
+    )
+    .await?;
+
+    if task.given_by == user {
+        given_tasks::command::open_task(ctx, task.id).await
+    } else if task.assigned_users.contains(&user)
+        || task.assigned_roles.iter().any(|r| roles.contains(r))
+    {
+        my_tasks::command::open_task(ctx, task.id).await
+    } else {
+        let embed =
+            given_tasks::embed::Embed::format_task(Embed::into_embed(), &task, &HashMap::new());
+        ctx.respond(ReadOnly(embed), true).await
+    }
+}
+
+#[async_trait]
+impl HandlerTrait for Handler {
+    async fn handle_prev(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if state.page == 0 {
+            return ctx.acknowlage().await;
+        }
+
+        state.page -= 1;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<SearchMsg<Handler>>().await
+    }
+
+    async fn handle_next(ctx: &mut EventCtx) -> Result {
+        let mut state = ctx.msg.clone_state::<State>().await.unwrap();
+        if state.page + 1 >= state.max_page() {
+            return ctx.acknowlage().await;
+        }
+
+        state.page += 1;
+        ctx.msg.write_state(state).await;
+        ctx.update_msg::<SearchMsg<Handler>>().await
+    }
+
+    async fn handle_open_1(ctx: &mut EventCtx) -> Result {
+        open(ctx, 0).await
+    }
+
+    async fn handle_open_2(ctx: &mut EventCtx) -> Result {
+        open(ctx, 1).await
+    }
+
+    async fn handle_open_3(ctx: &mut EventCtx) -> Result {
+        open(ctx, 2).await
+    }
+
+    async fn handle_open_4(ctx: &mut EventCtx) -> Result {
+        open(ctx, 3).await
+    }
+
+    async fn handle_open_5(ctx: &mut EventCtx) -> Result {
+        open(ctx, 4).await
+    }
+}
+
+/// Turns the command's options into a filter, or says which one couldn't be read
+async fn filter(ctx: &CommandCtx<'_>) -> TypedResult<std::result::Result<TaskFilter, String>> {
+    let options = TasksSearch::options(ctx);
+    let mut filter = TaskFilter {
+        text: options.text.map(str::to_owned),
+        giver: options.giver,
+        assignee: options.assignee,
+        completed: options.completed,
+        ..Default::default()
+    };
+
+    // everyone but the board sees only the tasks they have something to do with
+    let user = ctx.interaction.user.id;
+    let member = ctx.interaction.member.as_deref();
+    if !Capability::Board.allows(ctx.config, user, member, None) {
+        filter.visible_to = Some(user);
+        filter.visible_roles =
+            misc::user_roles(&ctx.discord_ctx.http, ctx.config, member, user).await?;
+    }
+
+    misc::task_filter(ctx, filter, options.from, options.to).await
+}
+
+#[async_trait]
+impl BotCommand for TasksCommand {
+    async fn run(&self, ctx: &CommandCtx) -> Result {
+        let filter = match filter(ctx).await? {
+            Ok(f) => f,
+            Err(e) => return ctx.respond(e, true).await,
+        };
+
+        let state = State {
+            page: 0,
+            results: ctx.db.search_tasks(&filter).await?,
+        };
+        let embeds = vec![SearchEmbed::create(&state)];
+        let mut msg =
+            InteractiveMessage::from_interaction::<SearchMsg<Handler>, State>(ctx, state, embeds)
+                .await?;
+        msg.handle_events(ctx).await
+    }
+
+    fn register(&self, create: CreateCommand) -> CreateCommand {
+        TasksSearch::new(create.description("Work with the club's tasks"))
+    }
+}
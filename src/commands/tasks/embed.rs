@@ -0,0 +1,72 @@
+use serenity::{
+    all::{CreateEmbed, CreateEmbedFooter},
+    async_trait,
+};
+
+use crate::{
+    commands::{misc::SEARCH_RESULTS_PER_PAGE, tasks::command::State},
+    components::{CommandCtx, EventCtx},
+    traits::{into_embed::IntoEmbedInteractive, IntoEmbed},
+};
+
+pub struct Embed;
+
+impl IntoEmbed for Embed {
+    fn into_embed() -> serenity::all::CreateEmbed {
+        CreateEmbed::new().color(serenity::model::colour::Color::MEIBE_PINK)
+    }
+}
+
+pub struct SearchEmbed;
+
+impl SearchEmbed {
+    pub fn create(state: &State) -> CreateEmbed {
+        let embed = Embed::into_embed().title("Search Results");
+        if state.results.is_empty() {
+            return embed.description("No tasks match");
+        }
+
+        let results = state
+            .results
+            .iter()
+            .skip(state.page * SEARCH_RESULTS_PER_PAGE)
+            .take(SEARCH_RESULTS_PER_PAGE)
+            .enumerate()
+            .map(|(i, task)| {
+                let mark = if task.completed { "✅ " } else { "" };
+                format!(
+                    "**{}.** {}{} (#{})\n{} • due <t:{}:R> • from <@{}>\n\n",
+                    i + 1,
+                    mark,
+                    task.title,
+                    task.id,
+                    task.status,
+                    task.deadline.timestamp(),
+                    task.given_by
+                )
+            })
+            .collect::<String>();
+
+        embed
+            .description(results)
+            .footer(CreateEmbedFooter::new(format!(
+                "{}/{} • {} tasks",
+                state.page + 1,
+                state.max_page(),
+                state.results.len()
+            )))
+    }
+}
+
+#[async_trait]
+impl IntoEmbedInteractive for SearchEmbed {
+    async fn from_command(_ctx: &CommandCtx, state: &crate::components::State) -> CreateEmbed {
+        let state = state.clone::<State>().await.unwrap();
+        Self::create(&state)
+    }
+
+    async fn from_event(ctx: &EventCtx) -> CreateEmbed {
+        let state = ctx.msg.clone_state::<State>().await.unwrap();
+        Self::create(&state)
+    }
+}
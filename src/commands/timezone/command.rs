@@ -0,0 +1,63 @@
+use chrono::Utc;
+use chrono_tz::Tz;
+use modal_macro::command;
+use serenity::{all::CreateCommand, async_trait};
+
+use crate::{
+    aliases::Result,
+    commands::misc,
+    components::CommandCtx,
+    traits::{BotCommand, Interactable},
+};
+
+command! {
+    <Timezone>
+        <description>"Show or set the timezone your dates are read in"</description>
+        <option required=false>
+            <name>"zone"</name>
+            <description>"IANA name like Europe/Warsaw, \"default\" goes back to the server one"</description>
+            <option_type>String</option_type>
+        </option>
+    </Timezone>
+}
+
+pub struct TimezoneCommand;
+
+#[async_trait]
+impl BotCommand for TimezoneCommand {
+    async fn run(&self, ctx: &CommandCtx) -> Result {
+        let user = ctx.interaction.user.id;
+        let zone = match Timezone::options(ctx).zone.map(str::trim) {
+            None => None,
+            Some("default") => {
+                ctx.db.set_user_timezone(user, None).await?;
+                None
+            }
+            Some(zone) => match zone.parse::<Tz>() {
+                Ok(tz) => {
+                    ctx.db.set_user_timezone(user, Some(tz)).await?;
+                    Some(tz)
+                }
+                Err(_) => return ctx.respond("Unknown timezone!", true).await,
+            },
+        };
+
+        let tz = match zone {
+            Some(tz) => tz,
+            None => misc::user_timezone(ctx.db, ctx.config, user).await?,
+        };
+        ctx.respond(
+            format!(
+                "Your dates are read in **{}**, it's {} there now",
+                tz.name(),
+                Utc::now().with_timezone(&tz).format("%H:%M %d-%m-%y")
+            ),
+            true,
+        )
+        .await
+    }
+
+    fn register(&self, create: CreateCommand) -> CreateCommand {
+        Timezone::new(create)
+    }
+}
@@ -1,12 +1,15 @@
 use serenity::all::{CacheHttp, CommandInteraction, Context};
 
-use crate::{calendar::CalendarHub, database::Db, traits::interactable::Interactable};
+use crate::{
+    calendar::CalendarHub, config::Config, database::Db, traits::interactable::Interactable,
+};
 
 pub struct CommandCtx<'ctx> {
     pub discord_ctx: &'ctx Context,
     pub interaction: &'ctx CommandInteraction,
     pub db: &'ctx Db,
     pub calendars: &'ctx CalendarHub,
+    pub config: &'ctx Config,
 }
 
 impl<'ctx> Interactable<'ctx> for CommandCtx<'ctx> {
@@ -2,6 +2,7 @@ use crate::{
     aliases::Result,
     calendar::CalendarHub,
     components::interactive_message::InteractiveMessage,
+    config::Config,
     database::Db,
     traits::{interactable::Interactable, InteractiveMessageTrait},
 };
@@ -13,10 +14,11 @@ pub struct EventCtx<'ctx> {
     pub msg: &'ctx mut InteractiveMessage,
     pub db: &'ctx Db,
     pub calendars: &'ctx CalendarHub,
+    pub config: &'ctx Config,
 }
 
 impl<'ctx> EventCtx<'ctx> {
-    pub async fn update_msg<T: InteractiveMessageTrait>(&mut self) -> Result {
+    pub async fn update_msg<T: InteractiveMessageTrait + 'static>(&mut self) -> Result {
         let embeds = T::with_embeds_event(self).await;
         self.msg
             .update_msg::<T>(self.discord_ctx, self.interaction, embeds)
@@ -1,5 +1,5 @@
 use serenity::all::{
-    ComponentInteraction, Context, CreateEmbed, CreateInteractionResponse,
+    Builder, ComponentInteraction, Context, CreateEmbed, CreateInteractionResponse,
     CreateInteractionResponseMessage, Message, MessageFlags,
 };
 use serenity::futures::StreamExt;
@@ -8,9 +8,39 @@ use std::time::Duration;
 use crate::aliases::{Result, TypedResult};
 use crate::calendar::CalendarHub;
 use crate::components::{CommandCtx, EventCtx, State};
+use crate::config::Config;
 use crate::database::Db;
 use crate::traits::state::StateTrait;
-use crate::traits::InteractiveMessageTrait;
+use crate::traits::{Interactable, InteractiveMessageTrait};
+
+type EventHandler = Box<
+    dyn for<'a> Fn(
+            &'a mut EventCtx<'a>,
+        ) -> std::pin::Pin<Box<dyn Future<Output = Result> + Send + 'a>>
+        + Send
+        + Sync,
+>;
+
+/// Runs the message's handler unless the user lacks the capability for what they pressed
+async fn authorized<T: InteractiveMessageTrait>(ctx: &mut EventCtx<'_>) -> Result {
+    let capability = T::capability(&ctx.interaction.data.custom_id);
+    let owner = ctx
+        .msg
+        .clone_state::<T::State>()
+        .await
+        .and_then(|state| state.task_owner());
+
+    if !capability.allows(
+        ctx.config,
+        ctx.interaction.user.id,
+        ctx.interaction.member.as_ref(),
+        owner,
+    ) {
+        return ctx.respond(capability.refusal(), true).await;
+    }
+
+    T::handle_event(ctx).await
+}
 
 pub struct InteractiveMessage {
     msg: Message,
@@ -19,16 +49,22 @@ pub struct InteractiveMessage {
     //i hate this
     stop: bool,
     has_handler_mutated: bool,
-    handler: Box<
-        dyn for<'a> Fn(
-                &'a mut EventCtx<'a>,
-            ) -> std::pin::Pin<Box<dyn Future<Output = Result> + Send + 'a>>
-            + Send
-            + Sync,
-    >,
+    handler: EventHandler,
 }
 
 impl InteractiveMessage {
+    /// Stands in for a message nothing listens to, e.g. a delivered reminder,
+    /// so its buttons can open views the way a live message's do
+    pub fn detached(msg: Message) -> Self {
+        Self {
+            msg,
+            state: State::_new_(()),
+            has_handler_mutated: false,
+            handler: Box::new(|_| Box::pin(async { Ok(()) })),
+            stop: true,
+        }
+    }
+
     pub async fn from_event<
         T: InteractiveMessageTrait + 'static,
         S: StateTrait + Send + Sync + 'static,
@@ -36,8 +72,12 @@ impl InteractiveMessage {
         ctx: &mut EventCtx<'_>,
         state: S,
     ) -> TypedResult<Self> {
-        let state = State::_new_(state);
-        let msg = T::into_msg().embeds(T::with_embeds_event(ctx).await);
+        let mut state = State::_new_(state);
+        // the embeds belong to the new message, so they're built from its state
+        std::mem::swap(&mut ctx.msg.state, &mut state);
+        let embeds = T::with_embeds_event(ctx).await;
+        std::mem::swap(&mut ctx.msg.state, &mut state);
+        let msg = T::into_msg().embeds(embeds);
 
         let builder = CreateInteractionResponse::Message(msg);
         ctx.interaction
@@ -47,14 +87,46 @@ impl InteractiveMessage {
 
         Ok(Self {
             msg: m,
-            state: state,
+            state,
+            has_handler_mutated: false,
+            handler: Box::new(|c| Box::pin(authorized::<T>(c))),
+            stop: false,
+        })
+    }
+
+    /// Responds to any interaction (e.g. a submitted modal) with a message.
+    /// There is no command or component interaction to build embeds from, so they're passed in.
+    pub async fn from_interaction<
+        'a,
+        T: InteractiveMessageTrait + 'static,
+        S: StateTrait + Send + Sync + 'static,
+    >(
+        ctx: &impl Interactable<'a>,
+        state: S,
+        embeds: Vec<CreateEmbed>,
+    ) -> TypedResult<Self> {
+        let state = State::_new_(state);
+        let (id, token) = ctx.id_token();
+
+        CreateInteractionResponse::Message(T::into_msg().embeds(embeds))
+            .execute(ctx.discord_ctx(), (id, token))
+            .await?;
+        let m = ctx
+            .discord_ctx()
+            .http
+            .get_original_interaction_response(token)
+            .await?;
+
+        Ok(Self {
+            msg: m,
+            state,
             has_handler_mutated: false,
-            handler: Box::new(|c| Box::pin(T::handle_event(c))),
+            handler: Box::new(|c| Box::pin(authorized::<T>(c))),
             stop: false,
         })
     }
 
-    pub async fn new<T: InteractiveMessageTrait<State: StateTrait + Send + Sync> + 'static>(
+    pub async fn new<T: InteractiveMessageTrait + 'static>(
         ctx: &CommandCtx<'_>,
     ) -> TypedResult<Self> {
         let state = State::init::<T::State>(ctx).await?;
@@ -66,14 +138,20 @@ impl InteractiveMessage {
 
         Ok(Self {
             msg: m,
-            state: state,
+            state,
             has_handler_mutated: false,
-            handler: Box::new(|c| Box::pin(T::handle_event(c))),
+            handler: Box::new(|c| Box::pin(authorized::<T>(c))),
             stop: false,
         })
     }
 
-    async fn _handle_events(&mut self, ctx: &Context, db: &Db, calendars: &CalendarHub) -> Result {
+    async fn _handle_events(
+        &mut self,
+        ctx: &Context,
+        db: &Db,
+        calendars: &CalendarHub,
+        config: &Config,
+    ) -> Result {
         let mut interaction_stream = self
             .msg
             .await_component_interaction(&ctx.shard)
@@ -89,8 +167,9 @@ impl InteractiveMessage {
                 discord_ctx: ctx,
                 interaction: &int,
                 msg: self,
-                db: db,
-                calendars: calendars,
+                db,
+                calendars,
+                config,
             };
 
             handler(&mut new_ctx).await?;
@@ -107,24 +186,23 @@ impl InteractiveMessage {
             self.handler = handler;
         }
 
-        match self.msg.flags {
-            Some(flags) => match flags {
+        if let Some(flags) = self.msg.flags {
+            match flags {
                 MessageFlags::EPHEMERAL => (), //you just can't delete emphemeral messages
                 _ => self.msg.delete(ctx).await?,
-            },
-            None => (),
+            }
         }
 
         Ok(())
     }
 
     pub async fn handle_events_from_event(&mut self, ctx: &EventCtx<'_>) -> Result {
-        self._handle_events(ctx.discord_ctx, ctx.db, ctx.calendars)
+        self._handle_events(ctx.discord_ctx, ctx.db, ctx.calendars, ctx.config)
             .await
     }
 
     pub async fn handle_events(&mut self, ctx: &CommandCtx<'_>) -> Result {
-        self._handle_events(ctx.discord_ctx, ctx.db, ctx.calendars)
+        self._handle_events(ctx.discord_ctx, ctx.db, ctx.calendars, ctx.config)
             .await
     }
 
@@ -132,7 +210,7 @@ impl InteractiveMessage {
         self.stop = true;
     }
 
-    pub async fn update_msg_modify<T: InteractiveMessageTrait>(
+    pub async fn update_msg_modify<T: InteractiveMessageTrait + 'static>(
         &mut self,
         ctx: &Context,
         interaction: &ComponentInteraction,
@@ -143,12 +221,12 @@ impl InteractiveMessage {
         interaction
             .create_response(ctx, CreateInteractionResponse::UpdateMessage(msg))
             .await?;
-        self.handler = Box::new(|c| Box::pin(T::handle_event(c)));
+        self.handler = Box::new(|c| Box::pin(authorized::<T>(c)));
         self.has_handler_mutated = true;
         Ok(())
     }
 
-    pub async fn update_msg<T: InteractiveMessageTrait>(
+    pub async fn update_msg<T: InteractiveMessageTrait + 'static>(
         &mut self,
         ctx: &Context,
         interaction: &ComponentInteraction,
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use chrono_tz::Tz;
+use serde::Deserialize;
+use serenity::all::{ChannelId, GuildId, RoleId};
+
+use crate::{aliases::TypedResult, calendar::CalendarSource, permissions::Capability};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Channel used for reminders sent as a `Discord Ping`
+    pub reminder_channel: Option<ChannelId>,
+    /// Dates typed by users who didn't pick their own timezone are read in this one
+    pub timezone: Tz,
+    /// Where the board hears about tasks that stay overdue
+    pub board_channel: Option<ChannelId>,
+    /// Days a task can stay overdue before it's escalated to `board_channel`, never when unset
+    pub escalate_after_days: Option<u32>,
+    /// Guild roles granting each capability, e.g. `{"board": [123]}`.
+    /// Server administrators are always admins, a capability without roles is theirs only.
+    pub roles: HashMap<Capability, Vec<RoleId>>,
+    /// Calendars shown in /events, `calendar.secret` is read when there are none
+    pub calendars: Vec<CalendarSource>,
+    /// Guild the calendar and custom events are mirrored to as scheduled events, none when unset
+    pub events_guild: Option<GuildId>,
+    /// Guild whose roles tasks are given to, asked for their members outside of it,
+    /// e.g. in DMs and the hourly job. Every guild the bot is in is asked when unset.
+    pub tasks_guild: Option<GuildId>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> TypedResult<Self> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
@@ -1,14 +1,20 @@
 use chrono::{DateTime, TimeZone, Utc};
-use serenity::all::UserId;
+use chrono_tz::Tz;
+use serenity::all::{GuildId, RoleId, ScheduledEventId, UserId};
 use sqlx::Row;
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, Sqlite, SqliteConnection};
 use std::collections::HashMap;
 
 use crate::calendar::Event;
 use crate::database::{ReminderGroup, Summary};
 use crate::{
     aliases::{Result, TypedResult},
-    database::{EventReminder, Reminder, ReminderWay, Task},
+    commands::misc,
+    database::{
+        Assignment, Blocker, ChecklistItem, Comment, DiscordEvent, DueEventReminder, DueReminder,
+        EventReminder, HistoryAction, HistoryEntry, Recurrence, RecurrenceUnit, Reminder,
+        ReminderWay, Task, TaskFilter, TaskStatus,
+    },
     log, log_error,
 };
 
@@ -64,9 +70,90 @@ impl Db {
         Ok(())
     }
 
-    pub async fn get_user_tasks(&self, discord_id: UserId) -> TypedResult<Vec<Task>> {
+    async fn record_history(
+        conn: &mut SqliteConnection,
+        task_id: i64,
+        actor: UserId,
+        action: HistoryAction,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Result {
+        let actor: i64 = actor.into();
+        let now = Utc::now().timestamp();
+        sqlx::query!(
+            r#"
+            INSERT INTO task_history (task_id, actor, action, before, after, created_unixtimestamp)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            task_id,
+            actor,
+            action,
+            before,
+            after,
+            now
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Every recorded change of the task, newest first
+    pub async fn get_task_history(&self, task_id: i64) -> TypedResult<Vec<HistoryEntry>> {
+        Ok(sqlx::query!(
+            r#"
+            SELECT actor, action as "action: HistoryAction", before, after, created_unixtimestamp
+            FROM task_history
+            WHERE task_id = ?
+            ORDER BY id DESC
+            "#,
+            task_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| HistoryEntry {
+            actor: UserId::new(row.actor.try_into().unwrap()),
+            action: row.action,
+            before: row.before,
+            after: row.after,
+            created: Utc.timestamp_opt(row.created_unixtimestamp, 0).unwrap(),
+        })
+        .collect())
+    }
+
+    pub async fn get_user_timezone(&self, discord_id: UserId) -> TypedResult<Option<Tz>> {
+        let id: i64 = discord_id.into();
+        Ok(
+            sqlx::query!(r#"SELECT timezone FROM users WHERE discord_id = ?"#, id)
+                .fetch_optional(&self.pool)
+                .await?
+                .and_then(|row| row.timezone)
+                .and_then(|tz| tz.parse().ok()),
+        )
+    }
+
+    pub async fn set_user_timezone(&self, discord_id: UserId, timezone: Option<Tz>) -> Result {
+        self.insert_user(discord_id).await?;
+        let id: i64 = discord_id.into();
+        let timezone = timezone.map(|tz| tz.name());
+        sqlx::query!(
+            r#"UPDATE users SET timezone = ? WHERE discord_id = ?"#,
+            timezone,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_user_tasks(
+        &self,
+        discord_id: UserId,
+        roles: &[RoleId],
+    ) -> TypedResult<Vec<Task>> {
         let id: i64 = discord_id.into();
         self.insert_user(discord_id).await?;
+        let roles = serde_json::to_string(&roles.iter().map(|r| r.get()).collect::<Vec<u64>>())?;
         let rows = sqlx::query!(
             r#"
         SELECT 
@@ -74,17 +161,24 @@ impl Db {
             t.title,
             t.description,
             t.completed,
+            t.status as "status: TaskStatus",
             t.deadline_unixtimestamp,
             t.given_by,
-            r.id AS reminder_id,
-            r.task AS reminder_task,
+            t.repeat_interval,
+            t.repeat_unit as "repeat_unit: RecurrenceUnit",
+            t.repeat_until,
+            r.id as "reminder_id: Option<i64>",
             r.when_unixtimestamp as "when: Option<i64>"
         FROM tasks t
-        JOIN task_targets tt ON t.id = tt.task_id
-        LEFT JOIN reminders r ON r.task = t.id AND r.user_id = tt.user_id
-        WHERE tt.user_id = ?
+        LEFT JOIN reminders r ON r.task = t.id AND r.user_id = ?1
+        WHERE t.id IN (SELECT task_id FROM task_targets WHERE user_id = ?1 AND via_role = 0)
+           OR t.id IN (
+                SELECT task_id FROM task_role_targets
+                WHERE role_id IN (SELECT value FROM json_each(?2))
+           )
         "#,
-            id
+            id,
+            roles
         )
         .fetch_all(&self.pool)
         .await?;
@@ -96,212 +190,1953 @@ impl Db {
                 id: row.task_id,
                 title: row.title,
                 completed: row.completed,
+                status: row.status,
                 description: row.description,
                 given_by: UserId::new(row.given_by.try_into().unwrap()),
                 deadline: Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap(),
+                recurrence: Recurrence::from_columns(
+                    row.repeat_interval,
+                    row.repeat_unit,
+                    row.repeat_until,
+                ),
                 reminders: Vec::new(),
                 assigned_users: Vec::new(),
+                assigned_roles: Vec::new(),
+                completed_by: Vec::new(),
+                checklist: Vec::new(),
+                blocked_by: Vec::new(),
             });
-            if let Some(when) = row.when {
+            if let (Some(id), Some(when)) = (row.reminder_id, row.when) {
                 task.reminders.push(Reminder {
+                    id,
                     when: chrono::Duration::new(when, 0).unwrap(),
                 });
             }
         }
 
+        let task_ids = group_map.keys().copied().collect::<Vec<_>>();
+        let mut completions = self.get_completions(&task_ids).await?;
+        let mut checklists = self.get_checklists(&task_ids).await?;
+        let mut blockers = self.get_blockers(&task_ids).await?;
+        for task in group_map.values_mut() {
+            task.completed_by = completions.remove(&task.id).unwrap_or_default();
+            task.checklist = checklists.remove(&task.id).unwrap_or_default();
+            task.blocked_by = blockers.remove(&task.id).unwrap_or_default();
+            task.reminders.sort_unstable_by_key(|r| r.when);
+        }
+
         Ok(group_map.into_values().collect())
     }
 
+    async fn get_completions(&self, task_ids: &[i64]) -> TypedResult<HashMap<i64, Vec<UserId>>> {
+        let ids = serde_json::to_string(task_ids)?;
+        let rows = sqlx::query!(
+            r#"
+        SELECT task_id, user_id FROM task_targets
+        WHERE completed = 1 AND task_id IN (SELECT value FROM json_each(?))
+        "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut completions: HashMap<i64, Vec<UserId>> = HashMap::new();
+        for row in rows {
+            completions
+                .entry(row.task_id)
+                .or_default()
+                .push(UserId::new(row.user_id.try_into().unwrap()));
+        }
+        Ok(completions)
+    }
+
+    /// Every reminder set on the tasks, as who set it and how long before the deadline
+    pub async fn get_task_reminders(
+        &self,
+        task_ids: &[i64],
+    ) -> TypedResult<HashMap<i64, Vec<(UserId, chrono::Duration)>>> {
+        let ids = serde_json::to_string(task_ids)?;
+        let rows = sqlx::query!(
+            r#"
+        SELECT task, user_id, when_unixtimestamp FROM reminders
+        WHERE task IN (SELECT value FROM json_each(?))
+        ORDER BY when_unixtimestamp DESC
+        "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut reminders: HashMap<i64, Vec<(UserId, chrono::Duration)>> = HashMap::new();
+        for row in rows {
+            reminders.entry(row.task).or_default().push((
+                UserId::new(row.user_id.try_into().unwrap()),
+                chrono::Duration::seconds(row.when_unixtimestamp),
+            ));
+        }
+        Ok(reminders)
+    }
+
+    async fn get_checklists(
+        &self,
+        task_ids: &[i64],
+    ) -> TypedResult<HashMap<i64, Vec<ChecklistItem>>> {
+        let ids = serde_json::to_string(task_ids)?;
+        let rows = sqlx::query!(
+            r#"
+        SELECT id, task_id, content, done_by FROM checklist_items
+        WHERE task_id IN (SELECT value FROM json_each(?))
+        ORDER BY position, id
+        "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut checklists: HashMap<i64, Vec<ChecklistItem>> = HashMap::new();
+        for row in rows {
+            checklists
+                .entry(row.task_id)
+                .or_default()
+                .push(ChecklistItem {
+                    id: row.id,
+                    content: row.content,
+                    done_by: row.done_by.map(|u| UserId::new(u.try_into().unwrap())),
+                });
+        }
+        Ok(checklists)
+    }
+
+    /// Appends the items to the end of the task's checklist
+    pub async fn add_checklist_items(
+        &self,
+        task_id: i64,
+        items: &[String],
+        actor: UserId,
+    ) -> TypedResult<Vec<ChecklistItem>> {
+        let mut trans = self.pool.begin().await?;
+        Self::record_history(
+            &mut trans,
+            task_id,
+            actor,
+            HistoryAction::ChecklistChanged,
+            None,
+            Some(format!("Added: {}", items.join(", "))),
+        )
+        .await?;
+        let mut added = vec![];
+        for content in items {
+            let id = sqlx::query!(
+                r#"
+                INSERT INTO checklist_items (task_id, position, content)
+                SELECT ?1, COALESCE(MAX(position) + 1, 0), ?2
+                FROM checklist_items WHERE task_id = ?1
+                "#,
+                task_id,
+                content
+            )
+            .execute(&mut *trans)
+            .await?
+            .last_insert_rowid();
+            added.push(ChecklistItem {
+                id,
+                content: content.clone(),
+                done_by: None,
+            });
+        }
+
+        trans.commit().await?;
+        Ok(added)
+    }
+
+    /// Ticks the item in the name of `actor`, or unticks it when `done` is false
+    pub async fn set_checklist_item_done(&self, item_id: i64, done: bool, actor: UserId) -> Result {
+        self.insert_user(actor).await?;
+        let done_by = done.then_some(i64::from(actor));
+        let mut trans = self.pool.begin().await?;
+        let row = sqlx::query!(
+            r#"UPDATE checklist_items SET done_by = ? WHERE id = ? RETURNING task_id, content"#,
+            done_by,
+            item_id
+        )
+        .fetch_one(&mut *trans)
+        .await?;
+
+        let (before, after) = match done {
+            true => ("⬜", "☑️"),
+            false => ("☑️", "⬜"),
+        };
+        Self::record_history(
+            &mut trans,
+            row.task_id,
+            actor,
+            HistoryAction::ChecklistChanged,
+            Some(format!("{} {}", before, row.content)),
+            Some(format!("{} {}", after, row.content)),
+        )
+        .await?;
+
+        trans.commit().await?;
+        Ok(())
+    }
+
+    pub async fn delete_checklist_item(&self, item_id: i64, actor: UserId) -> Result {
+        let mut trans = self.pool.begin().await?;
+        let row = sqlx::query!(
+            r#"DELETE FROM checklist_items WHERE id = ? RETURNING task_id, content"#,
+            item_id
+        )
+        .fetch_one(&mut *trans)
+        .await?;
+
+        Self::record_history(
+            &mut trans,
+            row.task_id,
+            actor,
+            HistoryAction::ChecklistChanged,
+            Some(format!("Removed: {}", row.content)),
+            None,
+        )
+        .await?;
+
+        trans.commit().await?;
+        Ok(())
+    }
+
+    async fn get_blockers(&self, task_ids: &[i64]) -> TypedResult<HashMap<i64, Vec<Blocker>>> {
+        let ids = serde_json::to_string(task_ids)?;
+        let rows = sqlx::query!(
+            r#"
+        SELECT d.task_id, t.id, t.title, t.status = 3 AS "done!: bool"
+        FROM task_dependencies d
+        JOIN tasks t ON t.id = d.blocked_by
+        WHERE d.task_id IN (SELECT value FROM json_each(?))
+        ORDER BY t.deadline_unixtimestamp
+        "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut blockers: HashMap<i64, Vec<Blocker>> = HashMap::new();
+        for row in rows {
+            blockers.entry(row.task_id).or_default().push(Blocker {
+                id: row.id,
+                title: row.title,
+                done: row.done,
+            });
+        }
+        Ok(blockers)
+    }
+
+    /// Whether making `task_id` wait for `blockers` would close a loop in the dependency graph
+    pub async fn would_create_cycle(&self, task_id: i64, blockers: &[i64]) -> TypedResult<bool> {
+        let ids = serde_json::to_string(blockers)?;
+        let row = sqlx::query!(
+            r#"
+            WITH RECURSIVE upstream(id) AS (
+                SELECT value FROM json_each(?1)
+                UNION
+                SELECT d.blocked_by FROM task_dependencies d
+                JOIN upstream u ON d.task_id = u.id
+            )
+            SELECT EXISTS (SELECT 1 FROM upstream WHERE id = ?2) AS "cycle: bool"
+            "#,
+            ids,
+            task_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.cycle)
+    }
+
+    /// Replaces the tasks blocking `task_id`, ids of tasks `actor` neither gave nor is assigned are skipped
+    pub async fn set_blockers(
+        &self,
+        task_id: i64,
+        blockers: &[i64],
+        actor: UserId,
+    ) -> TypedResult<Vec<Blocker>> {
+        let ids = serde_json::to_string(blockers)?;
+        let actor_id: i64 = actor.into();
+        let mut trans = self.pool.begin().await?;
+        let show = |ids: Vec<i64>| {
+            ids.iter()
+                .map(|id| format!("#{}", id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let before = show(Self::blocker_ids(&mut trans, task_id).await?);
+        sqlx::query!(
+            r#"DELETE FROM task_dependencies WHERE task_id = ?"#,
+            task_id
+        )
+        .execute(&mut *trans)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO task_dependencies (task_id, blocked_by)
+            SELECT ?1, id FROM tasks
+            WHERE id IN (SELECT value FROM json_each(?2))
+              AND (given_by = ?3
+                   OR EXISTS (SELECT 1 FROM task_targets WHERE task_id = tasks.id AND user_id = ?3))
+            "#,
+            task_id,
+            ids,
+            actor_id
+        )
+        .execute(&mut *trans)
+        .await?;
+        let after = show(Self::blocker_ids(&mut trans, task_id).await?);
+
+        Self::record_history(
+            &mut trans,
+            task_id,
+            actor,
+            HistoryAction::Edited,
+            Some(format!("Blocked by: {}", before)),
+            Some(format!("Blocked by: {}", after)),
+        )
+        .await?;
+        trans.commit().await?;
+
+        Ok(self
+            .get_blockers(&[task_id])
+            .await?
+            .remove(&task_id)
+            .unwrap_or_default())
+    }
+
+    async fn blocker_ids(conn: &mut SqliteConnection, task_id: i64) -> TypedResult<Vec<i64>> {
+        Ok(sqlx::query_scalar!(
+            r#"SELECT blocked_by FROM task_dependencies WHERE task_id = ? ORDER BY blocked_by"#,
+            task_id
+        )
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Tasks waiting on `task_id` that have no other open blockers left
+    pub async fn get_unblocked_by(&self, task_id: i64) -> TypedResult<Vec<(i64, String)>> {
+        Ok(sqlx::query!(
+            r#"
+            SELECT t.id, t.title FROM task_dependencies d
+            JOIN tasks t ON t.id = d.task_id
+            WHERE d.blocked_by = ?1
+              AND NOT EXISTS (
+                    SELECT 1 FROM task_dependencies o
+                    JOIN tasks b ON b.id = o.blocked_by
+                    WHERE o.task_id = d.task_id AND o.blocked_by != ?1 AND b.status != 3
+              )
+            "#,
+            task_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row.title))
+        .collect())
+    }
+
+    /// Comments on the task, newest first
+    pub async fn get_comments(&self, task_id: i64) -> TypedResult<Vec<Comment>> {
+        Ok(sqlx::query!(
+            r#"
+            SELECT id, author, content, created_unixtimestamp FROM task_comments
+            WHERE task_id = ?
+            ORDER BY created_unixtimestamp DESC, id DESC
+            "#,
+            task_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| Comment {
+            id: row.id,
+            author: UserId::new(row.author.try_into().unwrap()),
+            content: row.content,
+            created: Utc.timestamp_opt(row.created_unixtimestamp, 0).unwrap(),
+        })
+        .collect())
+    }
+
+    pub async fn add_comment(
+        &self,
+        task_id: i64,
+        author: UserId,
+        content: &str,
+    ) -> TypedResult<Comment> {
+        self.insert_user(author).await?;
+        let created = Utc::now();
+        let stamp = created.timestamp();
+        let author_id: i64 = author.into();
+        let id = sqlx::query!(
+            r#"
+            INSERT INTO task_comments (task_id, author, content, created_unixtimestamp)
+            VALUES (?, ?, ?, ?)
+            "#,
+            task_id,
+            author_id,
+            content,
+            stamp
+        )
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+
+        Ok(Comment {
+            id,
+            author,
+            content: content.to_owned(),
+            created,
+        })
+    }
+
+    /// Adds the offsets the user doesn't have on the task yet, returns the user's reminders on it
     pub async fn add_reminder(
         &self,
         task_id: i64,
         user_id: UserId,
         when: Vec<chrono::Duration>,
-    ) -> Result {
+    ) -> TypedResult<Vec<Reminder>> {
         let id: i64 = user_id.into();
         let mut trans = self.pool.begin().await?;
         for w in when {
             let secs = w.num_seconds();
+            let added = sqlx::query!(
+                r#"INSERT OR IGNORE INTO reminders (task, when_unixtimestamp, user_id) VALUES (?, ?, ?)"#,
+                task_id,
+                secs,
+                id
+            )
+            .execute(&mut *trans)
+            .await?
+            .rows_affected();
+            if added == 0 {
+                continue;
+            }
+
+            Self::record_history(
+                &mut trans,
+                task_id,
+                user_id,
+                HistoryAction::ReminderAdded,
+                None,
+                Some(format!("{} before", misc::format_offset(w))),
+            )
+            .await?;
+        }
+
+        trans.commit().await?;
+        self.get_reminders(task_id, user_id).await
+    }
+
+    /// The user's reminders on a task, the earliest before the deadline first
+    pub async fn get_reminders(&self, task_id: i64, user_id: UserId) -> TypedResult<Vec<Reminder>> {
+        let id: i64 = user_id.into();
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, when_unixtimestamp FROM reminders
+            WHERE task = ? AND user_id = ?
+            ORDER BY when_unixtimestamp
+            "#,
+            task_id,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Reminder {
+                id: row.id,
+                when: chrono::Duration::seconds(row.when_unixtimestamp),
+            })
+            .collect())
+    }
+
+    /// Deletes one of the user's reminders, someone else's can't be deleted this way
+    pub async fn delete_reminder(&self, reminder_id: i64, user_id: UserId) -> Result {
+        let id: i64 = user_id.into();
+        let mut trans = self.pool.begin().await?;
+        let deleted = sqlx::query!(
+            r#"DELETE FROM reminders WHERE id = ? AND user_id = ? RETURNING task, when_unixtimestamp"#,
+            reminder_id,
+            id
+        )
+        .fetch_optional(&mut *trans)
+        .await?;
+
+        if let Some(row) = deleted {
+            Self::record_history(
+                &mut trans,
+                row.task,
+                user_id,
+                HistoryAction::ReminderDeleted,
+                Some(format!(
+                    "{} before",
+                    misc::format_offset(chrono::Duration::seconds(row.when_unixtimestamp))
+                )),
+                None,
+            )
+            .await?;
+        }
+
+        trans.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_due_reminders(&self) -> TypedResult<Vec<DueReminder>> {
+        let now = Utc::now().timestamp();
+        Ok(sqlx::query!(
+            r#"
+        SELECT
+            r.id,
+            r.task,
+            r.user_id,
+            t.title,
+            t.deadline_unixtimestamp
+        FROM reminders r
+        JOIN tasks t ON t.id = r.task
+        WHERE r.sent = 0
+          AND t.status != 3
+          AND NOT EXISTS (
+                SELECT 1 FROM task_targets tt
+                WHERE tt.task_id = t.id AND tt.user_id = r.user_id AND tt.completed = 1
+          )
+          AND (
+                (r.snoozed_until_unixtimestamp IS NULL
+                    AND t.deadline_unixtimestamp > ?1
+                    AND t.deadline_unixtimestamp - r.when_unixtimestamp <= ?1)
+                OR r.snoozed_until_unixtimestamp <= ?1
+          )
+        "#,
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| DueReminder {
+            id: row.id,
+            task_id: row.task,
+            user_id: UserId::new(row.user_id.try_into().unwrap()),
+            title: row.title,
+            deadline: Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap(),
+        })
+        .collect())
+    }
+
+    pub async fn mark_reminder_sent(&self, reminder_id: i64) -> Result {
+        sqlx::query!(
+            r#"UPDATE reminders SET sent = 1, snoozed_until_unixtimestamp = NULL WHERE id = ?"#,
+            reminder_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Counts a delivery that reached the user in no way and has the reminder tried again,
+    /// unless that was its `max_attempts`th. Returns whether it was given up on.
+    pub async fn record_failed_reminder(
+        &self,
+        reminder_id: i64,
+        max_attempts: i64,
+    ) -> TypedResult<bool> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            UPDATE reminders
+            SET failed_attempts = failed_attempts + 1, sent = failed_attempts + 1 >= ?
+            WHERE id = ?
+            RETURNING sent
+            "#,
+            max_attempts,
+            reminder_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or(true))
+    }
+
+    /// Sends the user's reminder again at `until`, returns false when it doesn't exist anymore
+    pub async fn snooze_reminder(
+        &self,
+        reminder_id: i64,
+        user_id: UserId,
+        until: DateTime<Utc>,
+    ) -> TypedResult<bool> {
+        let id: i64 = user_id.into();
+        let until = until.timestamp();
+        let snoozed = sqlx::query!(
+            r#"
+            UPDATE reminders
+            SET sent = 0, failed_attempts = 0, snoozed_until_unixtimestamp = ?
+            WHERE id = ? AND user_id = ?
+            "#,
+            until,
+            reminder_id,
+            id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(snoozed != 0)
+    }
+
+    /// Everyone who asked to be reminded of upcoming events
+    pub async fn get_event_subscribers(&self) -> TypedResult<Vec<UserId>> {
+        let group = ReminderGroup::Events;
+        Ok(sqlx::query!(
+            r#"SELECT DISTINCT user_id FROM event_reminders WHERE reminder_group = ?"#,
+            group
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| UserId::new(row.user_id.try_into().unwrap()))
+        .collect())
+    }
+
+    /// Records the user's reminder about this occurrence of the event,
+    /// returns None when they were already reminded of it
+    pub async fn claim_event_reminder(
+        &self,
+        user_id: UserId,
+        event: &Event,
+    ) -> TypedResult<Option<DueEventReminder>> {
+        let id: i64 = user_id.into();
+        let uid = event.sync_uid();
+        let start = event.start.timestamp();
+        Ok(sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO event_reminders_sent (user_id, uid, summary, start_unixtimestamp)
+            VALUES (?, ?, ?, ?)
+            RETURNING id, uid, summary, start_unixtimestamp
+            "#,
+            id,
+            uid,
+            event.summary,
+            start
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| DueEventReminder {
+            id: row.id,
+            user_id,
+            uid: row.uid,
+            summary: row.summary,
+            start: Utc.timestamp_opt(row.start_unixtimestamp, 0).unwrap(),
+        }))
+    }
+
+    pub async fn get_snoozed_event_reminders(&self) -> TypedResult<Vec<DueEventReminder>> {
+        let now = Utc::now().timestamp();
+        Ok(sqlx::query!(
+            r#"
+            SELECT id, user_id, uid, summary, start_unixtimestamp
+            FROM event_reminders_sent
+            WHERE snoozed_until_unixtimestamp <= ?
+            "#,
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| DueEventReminder {
+            id: row.id,
+            user_id: UserId::new(row.user_id.try_into().unwrap()),
+            uid: row.uid,
+            summary: row.summary,
+            start: Utc.timestamp_opt(row.start_unixtimestamp, 0).unwrap(),
+        })
+        .collect())
+    }
+
+    pub async fn mark_event_reminder_sent(&self, reminder_id: i64) -> Result {
+        sqlx::query!(
+            r#"UPDATE event_reminders_sent SET snoozed_until_unixtimestamp = NULL WHERE id = ?"#,
+            reminder_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Sends the user's event reminder again at `until`, returns false when it's gone
+    pub async fn snooze_event_reminder(
+        &self,
+        reminder_id: i64,
+        user_id: UserId,
+        until: DateTime<Utc>,
+    ) -> TypedResult<bool> {
+        let id: i64 = user_id.into();
+        let until = until.timestamp();
+        let snoozed = sqlx::query!(
+            r#"
+            UPDATE event_reminders_sent
+            SET snoozed_until_unixtimestamp = ?
+            WHERE id = ? AND user_id = ?
+            "#,
+            until,
+            reminder_id,
+            id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(snoozed != 0)
+    }
+
+    pub async fn get_event_reminder(
+        &self,
+        reminder_id: i64,
+        user_id: UserId,
+    ) -> TypedResult<Option<DueEventReminder>> {
+        let id: i64 = user_id.into();
+        Ok(sqlx::query!(
+            r#"
+            SELECT id, uid, summary, start_unixtimestamp
+            FROM event_reminders_sent
+            WHERE id = ? AND user_id = ?
+            "#,
+            reminder_id,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| DueEventReminder {
+            id: row.id,
+            user_id,
+            uid: row.uid,
+            summary: row.summary,
+            start: Utc.timestamp_opt(row.start_unixtimestamp, 0).unwrap(),
+        }))
+    }
+
+    /// Nobody needs reminding of events that already started
+    pub async fn delete_past_event_reminders(&self) -> Result {
+        let now = Utc::now().timestamp();
+        sqlx::query!(
+            r#"DELETE FROM event_reminders_sent WHERE start_unixtimestamp < ?"#,
+            now
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_given_tasks(&self, discord_id: UserId) -> TypedResult<Vec<Task>> {
+        self.insert_user(discord_id).await?;
+        let id: i64 = discord_id.into();
+
+        let task_ids = sqlx::query_scalar!("SELECT id FROM tasks WHERE given_by = ?", id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        self.get_tasks(&task_ids).await
+    }
+
+    /// Finds tasks matching every filter that is set, soonest deadline first.
+    pub async fn search_tasks(&self, filter: &TaskFilter) -> TypedResult<Vec<Task>> {
+        // the text is matched literally, not as a LIKE pattern
+        let text = filter.text.as_ref().map(|t| {
+            let escaped = t
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        });
+        let giver = filter.giver.map(|u| u.get() as i64);
+        let assignee = filter.assignee.map(|u| u.get() as i64);
+        let roles = serde_json::to_string(
+            &filter
+                .assignee_roles
+                .iter()
+                .map(|r| r.get())
+                .collect::<Vec<u64>>(),
+        )?;
+        let from = filter.from.map(|d| d.timestamp());
+        let to = filter.to.map(|d| d.timestamp());
+        let viewer = filter.visible_to.map(|u| u.get() as i64);
+        let viewer_roles = serde_json::to_string(
+            &filter
+                .visible_roles
+                .iter()
+                .map(|r| r.get())
+                .collect::<Vec<u64>>(),
+        )?;
+
+        let task_ids = sqlx::query_scalar!(
+            r#"
+        SELECT id FROM tasks
+        WHERE (?1 IS NULL OR title LIKE ?1 ESCAPE '\' OR description LIKE ?1 ESCAPE '\')
+            AND (?2 IS NULL OR given_by = ?2)
+            AND (?3 IS NULL OR completed = ?3)
+            AND (?4 IS NULL OR deadline_unixtimestamp >= ?4)
+            AND (?5 IS NULL OR deadline_unixtimestamp <= ?5)
+            AND (?6 IS NULL
+                OR id IN (SELECT task_id FROM task_targets WHERE user_id = ?6 AND via_role = 0)
+                OR id IN (
+                    SELECT task_id FROM task_role_targets
+                    WHERE role_id IN (SELECT value FROM json_each(?7))
+                ))
+            AND (?8 IS NULL
+                OR given_by = ?8
+                OR id IN (SELECT task_id FROM task_targets WHERE user_id = ?8 AND via_role = 0)
+                OR id IN (
+                    SELECT task_id FROM task_role_targets
+                    WHERE role_id IN (SELECT value FROM json_each(?9))
+                ))
+        "#,
+            text,
+            giver,
+            filter.completed,
+            from,
+            to,
+            assignee,
+            roles,
+            viewer,
+            viewer_roles
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tasks = self.get_tasks(&task_ids).await?;
+        tasks.sort_by_key(|t| t.deadline);
+        Ok(tasks)
+    }
+
+    /// Loads tasks by id along with who they're assigned to, completions, checklists and blockers.
+    /// Reminders are per user, so they're left empty.
+    pub async fn get_tasks(&self, task_ids: &[i64]) -> TypedResult<Vec<Task>> {
+        // 1. If no tasks, return early
+        if task_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let ids = serde_json::to_string(task_ids)?;
+
+        // 2. Fetch the tasks themselves
+        let task_rows = sqlx::query!(
+            r#"
+        SELECT
+            id,
+            title,
+            description,
+            completed,
+            status as "status: TaskStatus",
+            deadline_unixtimestamp,
+            given_by,
+            repeat_interval,
+            repeat_unit as "repeat_unit: RecurrenceUnit",
+            repeat_until
+        FROM tasks WHERE id IN (SELECT value FROM json_each(?))
+        "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        // 3. Dynamically build the IN clause and bind parameters
+        let placeholders = task_ids
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("?{}", i + 1))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let sql = format!(
+            "SELECT task_id, user_id FROM task_targets WHERE via_role = 0 AND task_id IN ({})",
+            placeholders
+        );
+
+        // Build query with args
+        let mut query = sqlx::query(&sql);
+        for id in task_ids {
+            query = query.bind(id);
+        }
+
+        let target_rows = query.fetch_all(&self.pool).await?;
+
+        let sql = format!(
+            "SELECT task_id, role_id FROM task_role_targets WHERE task_id IN ({})",
+            placeholders
+        );
+
+        let mut query = sqlx::query(&sql);
+        for id in task_ids {
+            query = query.bind(id);
+        }
+
+        let role_rows = query.fetch_all(&self.pool).await?;
+
+        // 4. Map task_id => Vec<UserId> and task_id => Vec<RoleId>
+        let mut assignments: HashMap<i64, Vec<UserId>> = HashMap::new();
+        for row in target_rows {
+            // Since we used sqlx::query(), the fields need to be accessed by name
+            let task_id: i64 = row.try_get("task_id")?;
+            let user_id: i64 = row.try_get("user_id")?;
+            assignments
+                .entry(task_id)
+                .or_default()
+                .push(UserId::new(user_id as u64));
+        }
+
+        let mut role_assignments: HashMap<i64, Vec<RoleId>> = HashMap::new();
+        for row in role_rows {
+            let task_id: i64 = row.try_get("task_id")?;
+            let role_id: i64 = row.try_get("role_id")?;
+            role_assignments
+                .entry(task_id)
+                .or_default()
+                .push(RoleId::new(role_id as u64));
+        }
+
+        let mut completions = self.get_completions(task_ids).await?;
+        let mut checklists = self.get_checklists(task_ids).await?;
+        let mut blockers = self.get_blockers(task_ids).await?;
+
+        // 5. Assemble Task list
+        let tasks = task_rows
+            .into_iter()
+            .map(|row| Task {
+                id: row.id,
+                title: row.title,
+                description: row.description,
+                completed: row.completed,
+                status: row.status,
+                deadline: Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap(),
+                recurrence: Recurrence::from_columns(
+                    row.repeat_interval,
+                    row.repeat_unit,
+                    row.repeat_until,
+                ),
+                given_by: UserId::new(row.given_by as u64),
+                reminders: vec![],
+                assigned_users: assignments.remove(&row.id).unwrap_or_default(),
+                assigned_roles: role_assignments.remove(&row.id).unwrap_or_default(),
+                completed_by: completions.remove(&row.id).unwrap_or_default(),
+                checklist: checklists.remove(&row.id).unwrap_or_default(),
+                blocked_by: blockers.remove(&row.id).unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(tasks)
+    }
+
+    pub async fn get_assigned_users(&self, task_id: i64) -> TypedResult<Vec<UserId>> {
+        Ok(sqlx::query!(
+            r#"SELECT user_id FROM task_targets where task_id = ? AND via_role = 0"#,
+            task_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| UserId::new(row.user_id.try_into().unwrap()))
+                .collect()
+        })?)
+    }
+
+    pub async fn get_assigned_roles(&self, task_id: i64) -> TypedResult<Vec<RoleId>> {
+        Ok(sqlx::query!(
+            r#"SELECT role_id FROM task_role_targets where task_id = ?"#,
+            task_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| RoleId::new(row.role_id.try_into().unwrap()))
+                .collect()
+        })?)
+    }
+
+    pub async fn add_users_to_task(
+        &self,
+        task_id: i64,
+        users: Vec<UserId>,
+        actor: UserId,
+    ) -> Result {
+        if users.is_empty() {
+            return Ok(());
+        }
+        let mut transaction = self.pool.begin().await?;
+        Self::assign_users(&mut transaction, task_id, &users, actor).await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn assign_users(
+        conn: &mut SqliteConnection,
+        task_id: i64,
+        users: &[UserId],
+        actor: UserId,
+    ) -> Result {
+        let now = Utc::now().timestamp();
+        let added = users
+            .iter()
+            .map(|u| format!("<@{}>", u))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self::record_history(
+            &mut *conn,
+            task_id,
+            actor,
+            HistoryAction::Assigned,
+            None,
+            Some(added),
+        )
+        .await?;
+        for target in users {
+            let id: i64 = (*target).into();
+            sqlx::query!("INSERT OR IGNORE INTO users (discord_id) VALUES (?)", id)
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query!(
+                r#"
+                INSERT INTO task_targets (task_id, user_id, assigned_unixtimestamp)
+                VALUES (?, ?, ?)
+                ON CONFLICT (task_id, user_id) DO UPDATE SET via_role = 0
+                "#,
+                task_id,
+                id,
+                now
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn add_roles_to_task(
+        &self,
+        task_id: i64,
+        roles: Vec<RoleId>,
+        actor: UserId,
+    ) -> Result {
+        if roles.is_empty() {
+            return Ok(());
+        }
+        let mut transaction = self.pool.begin().await?;
+        let added = roles
+            .iter()
+            .map(|r| format!("<@&{}>", r))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self::record_history(
+            &mut transaction,
+            task_id,
+            actor,
+            HistoryAction::Assigned,
+            None,
+            Some(added),
+        )
+        .await?;
+        for role in roles {
+            let id: i64 = role.into();
+            sqlx::query!(
+                r#"INSERT OR IGNORE INTO task_role_targets (task_id, role_id) VALUES (?, ?)"#,
+                task_id,
+                id
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Creates the task with its assignees and the history entry, returns its id
+    async fn insert_task(conn: &mut SqliteConnection, new_task: &Task) -> TypedResult<i64> {
+        let timestamp = new_task.deadline.timestamp();
+        let id: i64 = new_task.given_by.into();
+        let (interval, unit, until) = Self::recurrence_columns(new_task.recurrence);
+        let now = Utc::now().timestamp();
+        let last_id = sqlx::query!(
+            r#"
+            INSERT INTO tasks (title, description, deadline_unixtimestamp, given_by, repeat_interval, repeat_unit, repeat_until, created_unixtimestamp)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            new_task.title,
+            new_task.description,
+            timestamp,
+            id,
+            interval,
+            unit,
+            until,
+            now
+        )
+        .execute(&mut *conn)
+        .await?.last_insert_rowid();
+
+        Self::record_history(
+            &mut *conn,
+            last_id,
+            new_task.given_by,
+            HistoryAction::Created,
+            None,
+            Some(Self::describe_task(new_task)),
+        )
+        .await?;
+
+        for target in &new_task.assigned_users {
+            let user_id: i64 = (*target).into();
+            sqlx::query!(
+                "INSERT OR IGNORE INTO users (discord_id) VALUES (?)",
+                user_id
+            )
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query!(
+                r#"
+                INSERT OR IGNORE INTO task_targets (task_id, user_id, assigned_unixtimestamp)
+                VALUES (?, ?, ?)
+                "#,
+                last_id,
+                user_id,
+                now
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        for role in &new_task.assigned_roles {
+            let role_id: i64 = (*role).into();
+            sqlx::query!(
+                r#"INSERT OR IGNORE INTO task_role_targets (task_id, role_id) VALUES (?, ?)"#,
+                last_id,
+                role_id
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(last_id)
+    }
+
+    /// Creates every task with its assignees, all of them or none
+    pub async fn import_tasks(&self, tasks: &[(Task, Vec<UserId>)], actor: UserId) -> Result {
+        let mut trans = self.pool.begin().await?;
+        for (task, assignees) in tasks {
+            let id = Self::insert_task(&mut trans, task).await?;
+            if !assignees.is_empty() {
+                Self::assign_users(&mut trans, id, assignees, actor).await?;
+            }
+        }
+        trans.commit().await?;
+        Ok(())
+    }
+
+    /// Inserts `new_task` together with its assignees, the id and progress fields are ignored
+    pub async fn add_task(&self, new_task: &Task) -> TypedResult<Task> {
+        let mut trans = self.pool.begin().await?;
+        let last_id = Self::insert_task(&mut trans, new_task).await?;
+
+        let row = sqlx::query!(
+            r#"
+        SELECT
+            id,
+            title,
+            description,
+            completed,
+            status as "status: TaskStatus",
+            deadline_unixtimestamp,
+            given_by,
+            repeat_interval,
+            repeat_unit as "repeat_unit: RecurrenceUnit",
+            repeat_until
+        FROM tasks WHERE id = ?
+        "#,
+            last_id
+        )
+        .fetch_one(&mut *trans)
+        .await?;
+
+        trans.commit().await?;
+
+        Ok(Task {
+            id: row.id,
+            title: row.title,
+            description: row.description,
+            completed: row.completed,
+            status: row.status,
+            deadline: Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap(),
+            recurrence: Recurrence::from_columns(
+                row.repeat_interval,
+                row.repeat_unit,
+                row.repeat_until,
+            ),
+            given_by: UserId::new(row.given_by.try_into().unwrap()),
+            reminders: vec![],
+            assigned_users: new_task.assigned_users.clone(),
+            assigned_roles: new_task.assigned_roles.clone(),
+            completed_by: vec![],
+            checklist: vec![],
+            blocked_by: vec![],
+        })
+    }
+
+    fn recurrence_columns(
+        recurrence: Option<Recurrence>,
+    ) -> (Option<i64>, Option<RecurrenceUnit>, Option<i64>) {
+        match recurrence {
+            Some(r) => (
+                Some(r.interval.into()),
+                Some(r.unit),
+                r.until.map(|u| u.timestamp()),
+            ),
+            None => (None, None, None),
+        }
+    }
+
+    /// Title, deadline and repeat rule as one line for the history log
+    fn describe_task(task: &Task) -> String {
+        let mut s = format!("{} (due <t:{}:f>)", task.title, task.deadline.timestamp());
+        if let Some(recurrence) = task.recurrence {
+            s += &format!(", {}", recurrence);
+        }
+        s
+    }
+
+    pub async fn edit_task(&self, new_task: &Task, actor: UserId) -> Result {
+        let timestamp = new_task.deadline.timestamp();
+        let (interval, unit, until) = Self::recurrence_columns(new_task.recurrence);
+        let mut trans = self.pool.begin().await?;
+
+        let old = sqlx::query!(
+            r#"
+            SELECT
+                title,
+                description,
+                deadline_unixtimestamp,
+                repeat_interval,
+                repeat_unit as "repeat_unit: RecurrenceUnit",
+                repeat_until
+            FROM tasks WHERE id = ?
+            "#,
+            new_task.id
+        )
+        .fetch_one(&mut *trans)
+        .await?;
+        let old_recurrence =
+            Recurrence::from_columns(old.repeat_interval, old.repeat_unit, old.repeat_until);
+        let show_recurrence = |r: Option<Recurrence>| match r {
+            Some(r) => r.to_string(),
+            None => "Never".to_owned(),
+        };
+
+        let mut before = vec![];
+        let mut after = vec![];
+        if old.title != new_task.title {
+            before.push(format!("Title: {}", old.title));
+            after.push(format!("Title: {}", new_task.title));
+        }
+        if old.description != new_task.description {
+            before.push(format!("Description: {}", old.description));
+            after.push(format!("Description: {}", new_task.description));
+        }
+        if old.deadline_unixtimestamp != timestamp {
+            before.push(format!("Deadline: <t:{}:f>", old.deadline_unixtimestamp));
+            after.push(format!("Deadline: <t:{}:f>", timestamp));
+        }
+        if (old.repeat_interval, old.repeat_unit, old.repeat_until) != (interval, unit, until) {
+            before.push(format!("Repeats: {}", show_recurrence(old_recurrence)));
+            after.push(format!("Repeats: {}", show_recurrence(new_task.recurrence)));
+        }
+
+        if !before.is_empty() {
+            Self::record_history(
+                &mut trans,
+                new_task.id,
+                actor,
+                HistoryAction::Edited,
+                Some(before.join("\n")),
+                Some(after.join("\n")),
+            )
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE tasks
+            SET title = ?, description = ?, deadline_unixtimestamp = ?, repeat_interval = ?, repeat_unit = ?, repeat_until = ?,
+                repeat_anchor = CASE
+                    WHEN deadline_unixtimestamp = ?3 AND repeat_interval IS ?4 AND repeat_unit IS ?5
+                    THEN repeat_anchor
+                END,
+                overdue_notified = overdue_notified AND deadline_unixtimestamp = ?3,
+                overdue_escalated = overdue_escalated AND deadline_unixtimestamp = ?3
+            WHERE id = ?
+            "#,
+            new_task.title,
+            new_task.description,
+            timestamp,
+            interval,
+            unit,
+            until,
+            new_task.id
+        )
+        .execute(&mut *trans)
+        .await?;
+
+        // reminders count back from the deadline, so a moved one has to remind again
+        if old.deadline_unixtimestamp != timestamp {
+            sqlx::query!(
+                r#"
+                UPDATE reminders SET sent = 0, failed_attempts = 0, snoozed_until_unixtimestamp = NULL
+                WHERE task = ?
+                "#,
+                new_task.id
+            )
+            .execute(&mut *trans)
+            .await?;
+        }
+
+        trans.commit().await?;
+        Ok(())
+    }
+
+    /// Creates the next occurrence of every repeating task that was finished or expired,
+    /// carrying over its assignees and reminder offsets
+    pub async fn generate_recurring_tasks(&self) -> Result {
+        let now = Utc::now();
+        let stamp = now.timestamp();
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                id,
+                title,
+                deadline_unixtimestamp,
+                given_by,
+                repeat_interval,
+                repeat_unit as "repeat_unit: RecurrenceUnit",
+                repeat_until,
+                COALESCE(repeat_anchor, deadline_unixtimestamp) AS "repeat_anchor!: i64"
+            FROM tasks
+            WHERE repeat_interval IS NOT NULL
+              AND repeat_generated = 0
+              AND (status = 3 OR deadline_unixtimestamp < ?)
+            "#,
+            stamp
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in rows {
+            let mut trans = self.pool.begin().await?;
+
+            sqlx::query!(
+                r#"UPDATE tasks SET repeat_generated = 1 WHERE id = ?"#,
+                row.id
+            )
+            .execute(&mut *trans)
+            .await?;
+
+            let Some(recurrence) =
+                Recurrence::from_columns(row.repeat_interval, row.repeat_unit, row.repeat_until)
+            else {
+                trans.commit().await?;
+                continue;
+            };
+
+            // skip the occurrences that already passed while nobody finished them
+            let deadline = Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap();
+            let anchor = Utc.timestamp_opt(row.repeat_anchor, 0).unwrap();
+            let next = recurrence.next(anchor, deadline.max(now));
+
+            if let Some(deadline) = next {
+                let deadline = deadline.timestamp();
+                let created = now.timestamp();
+                let new_id = sqlx::query!(
+                    r#"
+                    INSERT INTO tasks (title, description, deadline_unixtimestamp, given_by, repeat_interval, repeat_unit, repeat_until, repeat_anchor, created_unixtimestamp)
+                    SELECT title, description, ?, given_by, repeat_interval, repeat_unit, repeat_until, ?, ?
+                    FROM tasks WHERE id = ?
+                    "#,
+                    deadline,
+                    row.repeat_anchor,
+                    created,
+                    row.id
+                )
+                .execute(&mut *trans)
+                .await?
+                .last_insert_rowid();
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO task_targets (task_id, user_id, assigned_unixtimestamp)
+                    SELECT ?, user_id, ? FROM task_targets WHERE task_id = ? AND via_role = 0
+                    "#,
+                    new_id,
+                    created,
+                    row.id
+                )
+                .execute(&mut *trans)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO task_role_targets (task_id, role_id)
+                    SELECT ?, role_id FROM task_role_targets WHERE task_id = ?
+                    "#,
+                    new_id,
+                    row.id
+                )
+                .execute(&mut *trans)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO reminders (task, user_id, when_unixtimestamp)
+                    SELECT ?, user_id, when_unixtimestamp FROM reminders WHERE task = ?
+                    "#,
+                    new_id,
+                    row.id
+                )
+                .execute(&mut *trans)
+                .await?;
+
+                sqlx::query!(
+                    r#"
+                    INSERT INTO checklist_items (task_id, position, content)
+                    SELECT ?, position, content FROM checklist_items WHERE task_id = ?
+                    "#,
+                    new_id,
+                    row.id
+                )
+                .execute(&mut *trans)
+                .await?;
+
+                Self::record_history(
+                    &mut trans,
+                    new_id,
+                    UserId::new(row.given_by.try_into().unwrap()),
+                    HistoryAction::Created,
+                    None,
+                    Some(format!("Next occurrence of #{}", row.id)),
+                )
+                .await?;
+
+                log!(
+                    "Created the next occurrence of task {} ({})",
+                    row.id,
+                    row.title
+                );
+            }
+
+            trans.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Toggles completion for a single assignee. `role_members` are the current members of
+    /// the roles the task is assigned to, None when they couldn't be looked up, which leaves
+    /// the task's completion as it was. Returns whether every assignee is now done.
+    pub async fn toggle_task_completion(
+        &self,
+        task_id: i64,
+        user_id: UserId,
+        role_members: Option<&[UserId]>,
+    ) -> TypedResult<bool> {
+        let id: i64 = user_id.into();
+        let now = Utc::now().timestamp();
+        let mut trans = self.pool.begin().await?;
+
+        sqlx::query!("INSERT OR IGNORE INTO users (discord_id) VALUES (?)", id)
+            .execute(&mut *trans)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO task_targets (task_id, user_id, via_role, assigned_unixtimestamp)
+            SELECT id, ?, 1, created_unixtimestamp FROM tasks WHERE id = ?
+            "#,
+            id,
+            task_id
+        )
+        .execute(&mut *trans)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE task_targets
+            SET completed = NOT completed,
+                completed_unixtimestamp = CASE WHEN completed THEN NULL ELSE ? END
+            WHERE task_id = ? AND user_id = ?
+            "#,
+            now,
+            task_id,
+            id
+        )
+        .execute(&mut *trans)
+        .await?;
+
+        let done = sqlx::query_scalar!(
+            r#"SELECT completed FROM task_targets WHERE task_id = ? AND user_id = ?"#,
+            task_id,
+            id
+        )
+        .fetch_one(&mut *trans)
+        .await?;
+
+        let (before, after) = match done {
+            true => ("Not done", "Done"),
+            false => ("Done", "Not done"),
+        };
+        Self::record_history(
+            &mut trans,
+            task_id,
+            user_id,
+            HistoryAction::CompletionToggled,
+            Some(before.to_owned()),
+            Some(after.to_owned()),
+        )
+        .await?;
+
+        let completed = Self::update_completed(&mut trans, task_id, role_members).await?;
+
+        trans.commit().await?;
+        Ok(completed)
+    }
+
+    /// Marks the task completed once every assignee is done with it, returns whether it is
+    async fn update_completed(
+        conn: &mut SqliteConnection,
+        task_id: i64,
+        role_members: Option<&[UserId]>,
+    ) -> TypedResult<bool> {
+        let Some(role_members) = role_members else {
+            // there's no telling whether the role members are done
+            return Ok(
+                sqlx::query_scalar!(r#"SELECT completed FROM tasks WHERE id = ?"#, task_id)
+                    .fetch_one(&mut *conn)
+                    .await?,
+            );
+        };
+
+        let rows = sqlx::query!(
+            r#"SELECT user_id, completed, via_role FROM task_targets WHERE task_id = ?"#,
+            task_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let done = rows
+            .iter()
+            .filter(|row| row.completed)
+            .map(|row| UserId::new(row.user_id.try_into().unwrap()))
+            .collect::<Vec<_>>();
+
+        let assignees = rows
+            .iter()
+            .filter(|row| !row.via_role)
+            .map(|row| UserId::new(row.user_id.try_into().unwrap()))
+            .chain(role_members.iter().copied())
+            .collect::<Vec<_>>();
+
+        let completed = !assignees.is_empty() && assignees.iter().all(|user| done.contains(user));
+
+        // an approved task stays completed whoever joins or leaves it afterwards
+        Ok(sqlx::query_scalar!(
+            r#"UPDATE tasks SET completed = (? OR status = 3) WHERE id = ? RETURNING completed"#,
+            completed,
+            task_id
+        )
+        .fetch_one(&mut *conn)
+        .await?)
+    }
+
+    /// Takes users off a task along with their reminders and completion.
+    /// `role_members` are the members of the task's roles, needed to tell if it's completed now.
+    pub async fn remove_users_from_task(
+        &self,
+        task_id: i64,
+        users: &[UserId],
+        role_members: &[UserId],
+        actor: UserId,
+    ) -> Result {
+        if users.is_empty() {
+            return Ok(());
+        }
+        let mut trans = self.pool.begin().await?;
+        let removed = users
+            .iter()
+            .map(|u| format!("<@{}>", u))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self::record_history(
+            &mut trans,
+            task_id,
+            actor,
+            HistoryAction::Unassigned,
+            Some(removed),
+            None,
+        )
+        .await?;
+        for user in users {
+            let id: i64 = (*user).into();
             sqlx::query!(
-                r#"INSERT INTO reminders (task, when_unixtimestamp, user_id) VALUES (?, ?, ?)"#,
+                "DELETE FROM task_targets WHERE task_id = ? AND user_id = ?",
+                task_id,
+                id
+            )
+            .execute(&mut *trans)
+            .await?;
+            sqlx::query!(
+                "DELETE FROM reminders WHERE task = ? AND user_id = ?",
                 task_id,
-                secs,
                 id
             )
             .execute(&mut *trans)
             .await?;
         }
 
+        Self::update_completed(&mut trans, task_id, Some(role_members)).await?;
         trans.commit().await?;
         Ok(())
     }
 
-    pub async fn get_given_tasks(&self, discord_id: UserId) -> TypedResult<Vec<Task>> {
-        self.insert_user(discord_id).await?;
-        let id: i64 = discord_id.into();
+    /// Hands a task over from one user to another, the reminders they set move with it
+    pub async fn reassign_task(
+        &self,
+        task_id: i64,
+        from: UserId,
+        to: UserId,
+        role_members: &[UserId],
+        actor: UserId,
+    ) -> Result {
+        let from_id: i64 = from.into();
+        let to_id: i64 = to.into();
+        let now = Utc::now().timestamp();
+        let mut trans = self.pool.begin().await?;
 
-        // 1. Fetch tasks given by this user
-        let task_rows = sqlx::query!(
+        sqlx::query!("INSERT OR IGNORE INTO users (discord_id) VALUES (?)", to_id)
+            .execute(&mut *trans)
+            .await?;
+        sqlx::query!(
+            "DELETE FROM task_targets WHERE task_id = ? AND user_id = ?",
+            task_id,
+            from_id
+        )
+        .execute(&mut *trans)
+        .await?;
+        sqlx::query!(
             r#"
-        SELECT * FROM tasks WHERE given_by = ?
-        "#,
-            id
+            INSERT INTO task_targets (task_id, user_id, assigned_unixtimestamp)
+            VALUES (?, ?, ?)
+            ON CONFLICT (task_id, user_id) DO UPDATE SET via_role = 0
+            "#,
+            task_id,
+            to_id,
+            now
         )
-        .fetch_all(&self.pool)
+        .execute(&mut *trans)
         .await?;
 
-        let task_ids: Vec<i64> = task_rows.iter().map(|row| row.id).collect();
+        // the new assignee may have had the same reminders through a role
+        sqlx::query!(
+            r#"
+            DELETE FROM reminders
+            WHERE task = ?1 AND user_id = ?3 AND when_unixtimestamp IN (
+                SELECT when_unixtimestamp FROM reminders WHERE task = ?1 AND user_id = ?2
+            )
+            "#,
+            task_id,
+            from_id,
+            to_id
+        )
+        .execute(&mut *trans)
+        .await?;
+        sqlx::query!(
+            "UPDATE reminders SET user_id = ? WHERE task = ? AND user_id = ?",
+            to_id,
+            task_id,
+            from_id
+        )
+        .execute(&mut *trans)
+        .await?;
 
-        // 2. If no tasks, return early
-        if task_ids.is_empty() {
-            return Ok(vec![]);
-        }
+        Self::record_history(
+            &mut trans,
+            task_id,
+            actor,
+            HistoryAction::Reassigned,
+            Some(format!("<@{}>", from)),
+            Some(format!("<@{}>", to)),
+        )
+        .await?;
 
-        // 3. Dynamically build the IN clause and bind parameters
-        let placeholders = task_ids
-            .iter()
-            .enumerate()
-            .map(|(i, _)| format!("?{}", i + 1))
-            .collect::<Vec<_>>()
-            .join(", ");
+        Self::update_completed(&mut trans, task_id, Some(role_members)).await?;
+        trans.commit().await?;
+        Ok(())
+    }
 
-        let sql = format!(
-            "SELECT task_id, user_id FROM task_targets WHERE task_id IN ({})",
-            placeholders
-        );
+    /// Moves the task through the workflow and keeps the completions in line with it.
+    /// Done completes it for every assignee, `role_members` included, back to work clears them.
+    pub async fn set_task_status(
+        &self,
+        task_id: i64,
+        status: TaskStatus,
+        role_members: &[UserId],
+        actor: UserId,
+    ) -> Result {
+        let now = Utc::now().timestamp();
+        let mut trans = self.pool.begin().await?;
+        let old = sqlx::query!(
+            r#"SELECT status as "status: TaskStatus" FROM tasks WHERE id = ?"#,
+            task_id
+        )
+        .fetch_one(&mut *trans)
+        .await?;
+        sqlx::query!(
+            r#"UPDATE tasks SET status = ? WHERE id = ?"#,
+            status,
+            task_id
+        )
+        .execute(&mut *trans)
+        .await?;
 
-        // Build query with args
-        let mut query = sqlx::query(&sql);
-        for id in &task_ids {
-            query = query.bind(id);
+        match status {
+            TaskStatus::Done => {
+                for user in role_members {
+                    let id: i64 = (*user).into();
+                    sqlx::query!("INSERT OR IGNORE INTO users (discord_id) VALUES (?)", id)
+                        .execute(&mut *trans)
+                        .await?;
+                    sqlx::query!(
+                        r#"
+                        INSERT OR IGNORE INTO task_targets (task_id, user_id, via_role, assigned_unixtimestamp)
+                        SELECT id, ?, 1, created_unixtimestamp FROM tasks WHERE id = ?
+                        "#,
+                        id,
+                        task_id
+                    )
+                    .execute(&mut *trans)
+                    .await?;
+                }
+                sqlx::query!(
+                    r#"
+                    UPDATE task_targets
+                    SET completed = 1, completed_unixtimestamp = COALESCE(completed_unixtimestamp, ?)
+                    WHERE task_id = ? AND completed = 0
+                    "#,
+                    now,
+                    task_id
+                )
+                .execute(&mut *trans)
+                .await?;
+                sqlx::query!(r#"UPDATE tasks SET completed = 1 WHERE id = ?"#, task_id)
+                    .execute(&mut *trans)
+                    .await?;
+            }
+            TaskStatus::Todo | TaskStatus::InProgress => {
+                sqlx::query!(
+                    r#"
+                    UPDATE task_targets SET completed = 0, completed_unixtimestamp = NULL
+                    WHERE task_id = ?
+                    "#,
+                    task_id
+                )
+                .execute(&mut *trans)
+                .await?;
+                sqlx::query!(r#"UPDATE tasks SET completed = 0 WHERE id = ?"#, task_id)
+                    .execute(&mut *trans)
+                    .await?;
+            }
+            TaskStatus::InReview => (),
         }
 
-        let target_rows = query.fetch_all(&self.pool).await?;
+        Self::record_history(
+            &mut trans,
+            task_id,
+            actor,
+            HistoryAction::StatusChanged,
+            Some(old.status.to_string()),
+            Some(status.to_string()),
+        )
+        .await?;
 
-        // 4. Map task_id => Vec<UserId>
-        use std::collections::HashMap;
-        let mut assignments: HashMap<i64, Vec<UserId>> = HashMap::new();
-        for row in target_rows {
-            // Since we used sqlx::query(), the fields need to be accessed by name
-            let task_id: i64 = row.try_get("task_id")?;
-            let user_id: i64 = row.try_get("user_id")?;
-            assignments
-                .entry(task_id)
-                .or_default()
-                .push(UserId::new(user_id as u64));
-        }
+        trans.commit().await?;
+        Ok(())
+    }
 
-        // 5. Assemble Task list
-        let tasks = task_rows
-            .into_iter()
-            .map(|row| Task {
-                id: row.id,
-                title: row.title,
-                description: row.description,
-                completed: row.completed,
-                deadline: Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap(),
-                given_by: UserId::new(row.given_by as u64),
-                reminders: vec![],
-                assigned_users: assignments.remove(&row.id).unwrap_or_default(),
-            })
-            .collect();
+    pub async fn delete_task(&self, task_id: i64, actor: UserId) -> Result {
+        let mut trans = self.pool.begin().await?;
+        Self::archive_stats(&mut trans, &[task_id]).await?;
+        let title = sqlx::query!(r#"DELETE FROM tasks WHERE id = ? RETURNING title"#, task_id)
+            .fetch_one(&mut *trans)
+            .await?
+            .title;
 
-        Ok(tasks)
+        Self::record_history(
+            &mut trans,
+            task_id,
+            actor,
+            HistoryAction::Deleted,
+            Some(title),
+            None,
+        )
+        .await?;
+
+        trans.commit().await?;
+        Ok(())
     }
 
-    pub async fn get_assigned_users(&self, task_id: i64) -> TypedResult<Vec<UserId>> {
-        Ok(sqlx::query!(
-            r#"SELECT user_id FROM task_targets where task_id = ?"#,
-            task_id
+    /// Roles that tasks with a deadline in the range are assigned to
+    pub async fn get_roles_with_tasks(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> TypedResult<Vec<RoleId>> {
+        let from = from.map(|d| d.timestamp());
+        let to = to.map(|d| d.timestamp());
+        let roles = sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT rt.role_id
+            FROM task_role_targets rt
+            JOIN tasks t ON t.id = rt.task_id
+            WHERE (?1 IS NULL OR t.deadline_unixtimestamp >= ?1)
+              AND (?2 IS NULL OR t.deadline_unixtimestamp <= ?2)
+            "#,
+            from,
+            to
         )
         .fetch_all(&self.pool)
-        .await
-        .map(|rows| {
-            rows.into_iter()
-                .map(|row| UserId::new(row.user_id.try_into().unwrap()))
-                .collect()
-        })?)
-    }
-
-    pub async fn add_users_to_task(&self, task_id: i64, users: Vec<UserId>) -> Result {
-        let mut transaction = self.pool.begin().await?;
-        for target in users {
-            let id: i64 = target.into();
-            sqlx::query!("INSERT OR IGNORE INTO users (discord_id) VALUES (?)", id)
-                .execute(&mut *transaction)
-                .await?;
-            sqlx::query!(
-                r#"
-                INSERT OR IGNORE INTO task_targets (task_id, user_id)
-                VALUES (?, ?)
-                "#,
-                task_id,
-                id
-            )
-            .execute(&mut *transaction)
-            .await?;
-        }
+        .await?;
 
-        transaction.commit().await?;
-        Ok(())
+        Ok(roles
+            .into_iter()
+            .map(|r| RoleId::new(r.try_into().unwrap()))
+            .collect())
     }
 
-    pub async fn add_task(
+    /// Every assignment of tasks with a deadline in the range, deleted tasks included.
+    /// Members of an assigned role who never touched the task are only known from `role_members`.
+    pub async fn get_assignments(
         &self,
-        title: &str,
-        description: &str,
-        deadline: DateTime<Utc>,
-        given_by: UserId,
-    ) -> TypedResult<Task> {
-        let timestamp = deadline.timestamp();
-        let id: i64 = given_by.into();
-        let mut trans = self.pool.begin().await?;
-        let last_id = sqlx::query!(
-            r#"INSERT INTO tasks (title, description, deadline_unixtimestamp, given_by) VALUES (?, ?, ?, ?)"#,
-            title,
-            description,
-            timestamp,
-            id
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        role_members: &HashMap<RoleId, Vec<UserId>>,
+    ) -> TypedResult<Vec<Assignment>> {
+        let from = from.map(|d| d.timestamp());
+        let to = to.map(|d| d.timestamp());
+        let date = |stamp: i64| Utc.timestamp_opt(stamp, 0).unwrap();
+
+        let mut assignments = sqlx::query!(
+            r#"
+            SELECT
+                tt.task_id,
+                tt.user_id,
+                t.deadline_unixtimestamp,
+                tt.assigned_unixtimestamp,
+                tt.completed_unixtimestamp,
+                (t.status = 3 OR t.completed) AS "closed!: bool"
+            FROM task_targets tt
+            JOIN tasks t ON t.id = tt.task_id
+            WHERE (?1 IS NULL OR t.deadline_unixtimestamp >= ?1)
+              AND (?2 IS NULL OR t.deadline_unixtimestamp <= ?2)
+            "#,
+            from,
+            to
         )
-        .execute(&mut *trans)
-        .await?.last_insert_rowid();
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| Assignment {
+            task_id: row.task_id,
+            user_id: UserId::new(row.user_id.try_into().unwrap()),
+            deadline: date(row.deadline_unixtimestamp),
+            assigned: row.assigned_unixtimestamp.map(date),
+            completed: row.completed_unixtimestamp.map(date),
+            closed: row.closed,
+        })
+        .collect::<Vec<_>>();
 
-        let row = sqlx::query!(
+        let archived = sqlx::query!(
             r#"
-        SELECT * from tasks WHERE id = ?
-        "#,
-            last_id
+            SELECT task_id, user_id, deadline_unixtimestamp, assigned_unixtimestamp, completed_unixtimestamp
+            FROM task_stats_archive
+            WHERE (?1 IS NULL OR deadline_unixtimestamp >= ?1)
+              AND (?2 IS NULL OR deadline_unixtimestamp <= ?2)
+            "#,
+            from,
+            to
         )
-        .fetch_one(&mut *trans)
+        .fetch_all(&self.pool)
         .await?;
+        assignments.extend(archived.into_iter().map(|row| Assignment {
+            task_id: row.task_id,
+            user_id: UserId::new(row.user_id.try_into().unwrap()),
+            deadline: date(row.deadline_unixtimestamp),
+            assigned: row.assigned_unixtimestamp.map(date),
+            completed: row.completed_unixtimestamp.map(date),
+            closed: true,
+        }));
 
-        trans.commit().await?;
+        let role_targets = sqlx::query!(
+            r#"
+            SELECT
+                rt.task_id,
+                rt.role_id,
+                t.deadline_unixtimestamp,
+                t.created_unixtimestamp,
+                (t.status = 3 OR t.completed) AS "closed!: bool"
+            FROM task_role_targets rt
+            JOIN tasks t ON t.id = rt.task_id
+            WHERE (?1 IS NULL OR t.deadline_unixtimestamp >= ?1)
+              AND (?2 IS NULL OR t.deadline_unixtimestamp <= ?2)
+            "#,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in role_targets {
+            let role = RoleId::new(row.role_id.try_into().unwrap());
+            for user in role_members.get(&role).into_iter().flatten() {
+                if assignments
+                    .iter()
+                    .any(|a| a.task_id == row.task_id && a.user_id == *user)
+                {
+                    continue;
+                }
+                assignments.push(Assignment {
+                    task_id: row.task_id,
+                    user_id: *user,
+                    deadline: date(row.deadline_unixtimestamp),
+                    assigned: row.created_unixtimestamp.map(date),
+                    completed: None,
+                    closed: row.closed,
+                });
+            }
+        }
 
-        Ok(Task {
-            id: row.id,
-            title: row.title,
-            description: row.description,
-            completed: row.completed,
-            deadline: Utc.timestamp_opt(row.deadline_unixtimestamp, 0).unwrap(),
-            given_by: UserId::new(row.given_by.try_into().unwrap()),
-            reminders: vec![],
-            assigned_users: vec![],
-        })
+        Ok(assignments)
     }
 
-    pub async fn edit_task(&self, new_task: &Task) -> Result {
-        let timestamp = new_task.deadline.timestamp();
-        sqlx::query!(r#"UPDATE tasks SET title = ?, description = ?, deadline_unixtimestamp = ? WHERE id = ?"#, new_task.title, new_task.description, timestamp, new_task.id).execute(&self.pool).await?;
-        Ok(())
+    /// Unfinished tasks whose deadline passed since the last check
+    pub async fn get_newly_overdue_tasks(&self) -> TypedResult<Vec<Task>> {
+        let now = Utc::now().timestamp();
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM tasks
+            WHERE deadline_unixtimestamp < ? AND status != 3 AND completed = 0
+              AND overdue_notified = 0
+            "#,
+            now
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        self.get_tasks(&ids).await
     }
 
-    pub async fn toggle_task_completion(&self, task_id: i64) -> Result {
+    pub async fn mark_overdue_notified(&self, task_id: i64) -> Result {
         sqlx::query!(
-            r#"
-            UPDATE tasks
-            SET completed = NOT completed
-            WHERE id = ?
-            "#,
+            "UPDATE tasks SET overdue_notified = 1 WHERE id = ?",
             task_id
         )
         .execute(&self.pool)
@@ -309,24 +2144,74 @@ impl Db {
         Ok(())
     }
 
-    pub async fn delete_task(&self, task_id: i64) -> Result {
-        sqlx::query!(r#"DELETE FROM tasks WHERE id = ?"#, task_id)
-            .execute(&self.pool)
-            .await?;
+    /// Unfinished tasks that are still overdue `after` their deadline and weren't escalated yet
+    pub async fn get_tasks_to_escalate(&self, after: chrono::Duration) -> TypedResult<Vec<Task>> {
+        let before = (Utc::now() - after).timestamp();
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM tasks
+            WHERE deadline_unixtimestamp < ? AND status != 3 AND completed = 0
+              AND overdue_escalated = 0
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        self.get_tasks(&ids).await
+    }
+
+    pub async fn mark_overdue_escalated(&self, task_id: i64) -> Result {
+        sqlx::query!(
+            "UPDATE tasks SET overdue_escalated = 1 WHERE id = ?",
+            task_id
+        )
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
 
     pub async fn delete_completed_expired_tasks(&self) -> Result {
         let now = Utc::now().timestamp();
-        sqlx::query!(
+        let mut trans = self.pool.begin().await?;
+        let ids = sqlx::query_scalar!(
             r#"
-            DELETE FROM tasks
-            WHERE completed = 1
+            SELECT id FROM tasks
+            WHERE status = 3
               AND deadline_unixtimestamp < ?
             "#,
             now
         )
-        .execute(&self.pool)
+        .fetch_all(&mut *trans)
+        .await?;
+
+        Self::archive_stats(&mut trans, &ids).await?;
+        let ids = serde_json::to_string(&ids)?;
+        sqlx::query!(
+            r#"DELETE FROM tasks WHERE id IN (SELECT value FROM json_each(?))"#,
+            ids
+        )
+        .execute(&mut *trans)
+        .await?;
+
+        trans.commit().await?;
+        Ok(())
+    }
+
+    /// Keeps the assignments of tasks about to be deleted, so they still count in the statistics
+    async fn archive_stats(conn: &mut SqliteConnection, task_ids: &[i64]) -> Result {
+        let ids = serde_json::to_string(task_ids)?;
+        sqlx::query!(
+            r#"
+            INSERT OR REPLACE INTO task_stats_archive
+                (task_id, user_id, deadline_unixtimestamp, assigned_unixtimestamp, completed_unixtimestamp)
+            SELECT tt.task_id, tt.user_id, t.deadline_unixtimestamp, tt.assigned_unixtimestamp, tt.completed_unixtimestamp
+            FROM task_targets tt
+            JOIN tasks t ON t.id = tt.task_id
+            WHERE tt.task_id IN (SELECT value FROM json_each(?))
+            "#,
+            ids
+        )
+        .execute(&mut *conn)
         .await?;
         Ok(())
     }
@@ -441,14 +2326,82 @@ impl Db {
             .map(|rows| {
                 rows.into_iter()
                     .map(|row| Event {
-                        uid: 0.to_string(),
+                        uid: format!("custom-{}", row.id),
                         summary: row.summary,
                         start: Utc.timestamp_opt(row.start, 0).unwrap(),
+                        ..Default::default()
                     })
                     .collect()
             })?)
     }
 
+    pub async fn get_discord_events(&self, guild: GuildId) -> TypedResult<Vec<DiscordEvent>> {
+        let guild = i64::from(guild);
+        Ok(
+            sqlx::query!(r#"SELECT * FROM discord_events WHERE guild_id = ?"#, guild)
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|row| DiscordEvent {
+                    source: row.source,
+                    uid: row.uid,
+                    discord_event_id: ScheduledEventId::new(
+                        row.discord_event_id.try_into().unwrap(),
+                    ),
+                    summary: row.summary,
+                    start: Utc.timestamp_opt(row.start_unixtimestamp, 0).unwrap(),
+                    end: Utc.timestamp_opt(row.end_unixtimestamp, 0).unwrap(),
+                    location: row.location,
+                })
+                .collect(),
+        )
+    }
+
+    /// Remembers the guild event made for `event.uid` of `event.source`, or what it was edited to
+    pub async fn set_discord_event(&self, guild: GuildId, event: &DiscordEvent) -> Result {
+        let guild = i64::from(guild);
+        let discord_event_id = i64::from(event.discord_event_id);
+        let start = event.start.timestamp();
+        let end = event.end.timestamp();
+        sqlx::query!(
+            r#"
+            INSERT INTO discord_events
+                (source, uid, guild_id, discord_event_id, summary, start_unixtimestamp, end_unixtimestamp, location)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (source, uid, guild_id) DO UPDATE SET
+                discord_event_id = excluded.discord_event_id,
+                summary = excluded.summary,
+                start_unixtimestamp = excluded.start_unixtimestamp,
+                end_unixtimestamp = excluded.end_unixtimestamp,
+                location = excluded.location
+            "#,
+            event.source,
+            event.uid,
+            guild,
+            discord_event_id,
+            event.summary,
+            start,
+            end,
+            event.location
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_discord_event(&self, guild: GuildId, source: &str, uid: &str) -> Result {
+        let guild = i64::from(guild);
+        sqlx::query!(
+            r#"DELETE FROM discord_events WHERE source = ? AND uid = ? AND guild_id = ?"#,
+            source,
+            uid,
+            guild
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_summaries(&self) -> TypedResult<Vec<Summary>> {
         Ok(sqlx::query_as!(Summary, r#"SELECT * FROM summaries"#)
             .fetch_all(&self.pool)
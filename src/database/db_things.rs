@@ -1,16 +1,28 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
-use chrono::Utc;
+use chrono::{DateTime, Days, Months, TimeZone, Utc};
 use modal_macro::Selection;
-use serenity::all::{ChannelId, Http, UserId};
+use serenity::all::{
+    ChannelId, CreateEmbed, CreateMessage, Http, RoleId, ScheduledEventId, UserId,
+};
 
-use crate::{aliases::Result, traits::IntoMessage};
+use crate::{
+    aliases::Result, commands::misc, handler::reminder_buttons, log_warn, traits::IntoMessage,
+};
 
 #[derive(Debug, Clone)]
 pub struct Reminder {
+    pub id: i64,
+    /// How long before the deadline
     pub when: chrono::Duration,
 }
 
+impl Display for Reminder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} before", misc::format_offset(self.when))
+    }
+}
+
 #[derive(Selection, Debug, sqlx::Type, Clone, Copy)]
 #[repr(u8)]
 #[sqlx(type_name = "INTEGER")]
@@ -34,7 +46,7 @@ impl Display for ReminderWay {
     }
 }
 
-#[derive(Debug, sqlx::Type, Clone, Copy)]
+#[derive(Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 #[sqlx(type_name = "INTEGER")]
 pub enum ReminderGroup {
@@ -66,6 +78,184 @@ impl ReminderGroup {
     }
 }
 
+#[derive(Selection, Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+#[sqlx(type_name = "INTEGER")]
+pub enum TaskStatus {
+    #[select_value("To Do")]
+    Todo = 0,
+    #[select_value("In Progress")]
+    InProgress = 1,
+    #[select_value("In Review")]
+    InReview = 2,
+    Done = 3,
+}
+
+impl Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Todo => "To Do",
+            Self::InProgress => "In Progress",
+            Self::InReview => "In Review",
+            Self::Done => "Done",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+impl TaskStatus {
+    /// Assignees move the task around freely up to review,
+    /// only the task giver can approve it to done or reopen it.
+    pub fn can_move_to(self, to: Self, by_giver: bool) -> bool {
+        match (self, to) {
+            (from, to) if from == to => false,
+            (Self::InReview, Self::Done) => by_giver,
+            (_, Self::Done) => false,
+            (Self::Done, _) => by_giver,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+#[sqlx(type_name = "INTEGER")]
+pub enum HistoryAction {
+    Created = 0,
+    Edited = 1,
+    Assigned = 2,
+    CompletionToggled = 3,
+    StatusChanged = 4,
+    ReminderAdded = 5,
+    ReminderDeleted = 6,
+    ChecklistChanged = 7,
+    Deleted = 8,
+    Unassigned = 9,
+    Reassigned = 10,
+}
+
+impl Display for HistoryAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Created => "Created",
+            Self::Edited => "Edited",
+            Self::Assigned => "Assigned",
+            Self::CompletionToggled => "Completion toggled",
+            Self::StatusChanged => "Status changed",
+            Self::ReminderAdded => "Reminder added",
+            Self::ReminderDeleted => "Reminder deleted",
+            Self::ChecklistChanged => "Checklist changed",
+            Self::Deleted => "Deleted",
+            Self::Unassigned => "Unassigned",
+            Self::Reassigned => "Reassigned",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub actor: UserId,
+    pub action: HistoryAction,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub created: DateTime<Utc>,
+}
+
+impl Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<t:{}:f> **{}** by <@{}>",
+            self.created.timestamp(),
+            self.action,
+            self.actor
+        )?;
+        if let Some(before) = &self.before {
+            write!(f, "\nBefore: {}", before)?;
+        }
+        if let Some(after) = &self.after {
+            write!(f, "\nAfter: {}", after)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, sqlx::Type, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+#[sqlx(type_name = "INTEGER")]
+pub enum RecurrenceUnit {
+    Days = 0,
+    Weeks = 1,
+    Months = 2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Recurrence {
+    pub interval: u32,
+    pub unit: RecurrenceUnit,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Recurrence {
+    pub fn from_columns(
+        interval: Option<i64>,
+        unit: Option<RecurrenceUnit>,
+        until: Option<i64>,
+    ) -> Option<Self> {
+        Some(Self {
+            interval: interval?.try_into().ok()?,
+            unit: unit?,
+            until: until.and_then(|u| Utc.timestamp_opt(u, 0).single()),
+        })
+    }
+
+    /// The deadline of the `n`th occurrence after the first one, due at `anchor`
+    fn nth(&self, anchor: DateTime<Utc>, n: u32) -> Option<DateTime<Utc>> {
+        let steps = self.interval.checked_mul(n)?;
+        match self.unit {
+            RecurrenceUnit::Days => anchor.checked_add_days(Days::new(steps.into())),
+            RecurrenceUnit::Weeks => anchor.checked_add_days(Days::new(u64::from(steps) * 7)),
+            RecurrenceUnit::Months => anchor.checked_add_months(Months::new(steps)),
+        }
+    }
+
+    /// The first occurrence due after `after`, counted from the first one due at `anchor`
+    /// so a task on the 31st comes back to it after shorter months. `None` once the rule has ended
+    pub fn next(&self, anchor: DateTime<Utc>, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let next = (1..)
+            .map(|n| self.nth(anchor, n))
+            .find(|next| next.is_none_or(|next| next > after))??;
+
+        match self.until {
+            Some(until) if next > until => None,
+            _ => Some(next),
+        }
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unit = match self.unit {
+            RecurrenceUnit::Days => "day",
+            RecurrenceUnit::Weeks => "week",
+            RecurrenceUnit::Months => "month",
+        };
+
+        match self.interval {
+            1 => write!(f, "Every {unit}")?,
+            n => write!(f, "Every {n} {unit}s")?,
+        }
+
+        match self.until {
+            Some(until) => write!(f, " until <t:{}:D>", until.timestamp()),
+            None => Ok(()),
+        }
+    }
+}
+
 #[derive(Debug, sqlx::FromRow, Clone)]
 pub struct EventReminder {
     pub user_id: UserId,
@@ -74,16 +264,280 @@ pub struct EventReminder {
     pub group: ReminderGroup,
 }
 
+impl EventReminder {
+    pub async fn send(
+        &self,
+        http: &Http,
+        ping_channel: Option<ChannelId>,
+        msg: &impl IntoMessage,
+    ) -> Result {
+        match (self.way, ping_channel) {
+            (ReminderWay::DiscordPing, Some(channel)) => {
+                channel
+                    .send_message(http, msg.into_msg().content(format!("<@{}>", self.user_id)))
+                    .await?;
+            }
+            (ReminderWay::DiscordPing, None) | (ReminderWay::DirectMsg, _) => {
+                self.user_id.direct_message(http, msg.into_msg()).await?;
+            }
+            (ReminderWay::Email, _) => {
+                log_warn!(
+                    "Email reminders are not supported yet, skipping {}",
+                    self.user_id
+                )
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A guild scheduled event mirroring a calendar or custom event, as it was last synced
+#[derive(Clone, Debug)]
+pub struct DiscordEvent {
+    /// The calendar source's name, empty for custom events
+    pub source: String,
+    /// See [`crate::calendar::Event::sync_uid`]
+    pub uid: String,
+    pub discord_event_id: ScheduledEventId,
+    pub summary: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub location: String,
+}
+
+impl DiscordEvent {
+    /// Whether the guild event has to be edited to look like `other`
+    pub fn differs(&self, other: &Self) -> bool {
+        self.summary != other.summary
+            || self.start != other.start
+            || self.end != other.end
+            || self.location != other.location
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DueReminder {
+    pub id: i64,
+    pub task_id: i64,
+    pub user_id: UserId,
+    pub title: String,
+    pub deadline: chrono::DateTime<Utc>,
+}
+
+impl IntoMessage for DueReminder {
+    fn into_msg(&self) -> CreateMessage {
+        CreateMessage::new()
+            .embed(
+                CreateEmbed::new()
+                    .color(serenity::model::Colour::MEIBE_PINK)
+                    .title(format!("Reminder: {}", self.title))
+                    .field(
+                        "Deadline",
+                        format!("<t:{0}:f> (<t:{0}:R>)", self.deadline.timestamp()),
+                        false,
+                    ),
+            )
+            .components(vec![reminder_buttons::buttons(self.task_id, self.id)])
+    }
+}
+
+/// A reminder about an upcoming calendar or custom event
+#[derive(Clone, Debug)]
+pub struct DueEventReminder {
+    pub id: i64,
+    pub user_id: UserId,
+    /// The event's [`crate::calendar::Event::sync_uid`]
+    pub uid: String,
+    pub summary: String,
+    pub start: chrono::DateTime<Utc>,
+}
+
+impl IntoMessage for DueEventReminder {
+    fn into_msg(&self) -> CreateMessage {
+        CreateMessage::new()
+            .embed(
+                CreateEmbed::new()
+                    .color(serenity::model::Colour::MEIBE_PINK)
+                    .title(format!("Upcoming: {}", self.summary))
+                    .field(
+                        "Starts",
+                        format!("<t:{0}:f> (<t:{0}:R>)", self.start.timestamp()),
+                        false,
+                    ),
+            )
+            .components(vec![reminder_buttons::event_buttons(self.id)])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ChecklistItem {
+    pub id: i64,
+    pub content: String,
+    pub done_by: Option<UserId>,
+}
+
+impl Display for ChecklistItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.done_by {
+            Some(user) => write!(f, "☑️ ~~{}~~ (<@{}>)", self.content, user),
+            None => write!(f, "⬜ {}", self.content),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Blocker {
+    pub id: i64,
+    pub title: String,
+    pub done: bool,
+}
+
+impl Display for Blocker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.done {
+            true => write!(f, "☑️ ~~#{} {}~~", self.id, self.title),
+            false => write!(f, "⬜ #{} {}", self.id, self.title),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Comment {
+    pub id: i64,
+    pub author: UserId,
+    pub content: String,
+    pub created: DateTime<Utc>,
+}
+
+impl Display for Comment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<@{}> <t:{}:R>\n{}",
+            self.author,
+            self.created.timestamp(),
+            self.content
+        )
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Task {
     pub id: i64,
     pub title: String,
     pub description: String,
     pub completed: bool,
+    pub status: TaskStatus,
     pub deadline: chrono::DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
     pub given_by: UserId,
     pub reminders: Vec<Reminder>,
     pub assigned_users: Vec<UserId>,
+    pub assigned_roles: Vec<RoleId>,
+    pub completed_by: Vec<UserId>,
+    pub checklist: Vec<ChecklistItem>,
+    pub blocked_by: Vec<Blocker>,
+}
+
+/// One member's part in a task, all statistics are counted from these
+#[derive(Clone, Debug)]
+pub struct Assignment {
+    pub task_id: i64,
+    pub user_id: UserId,
+    pub deadline: DateTime<Utc>,
+    /// Unknown for tasks created before this was recorded
+    pub assigned: Option<DateTime<Utc>>,
+    pub completed: Option<DateTime<Utc>>,
+    /// The task is done or deleted, so it can't be overdue anymore
+    pub closed: bool,
+}
+
+impl Assignment {
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.completed.is_none() && !self.closed && self.deadline < now
+    }
+}
+
+/// Filters for looking tasks up, unset ones match everything
+#[derive(Clone, Debug, Default)]
+pub struct TaskFilter {
+    /// Part of the title or description
+    pub text: Option<String>,
+    pub giver: Option<UserId>,
+    /// Matches tasks assigned to the user directly or through one of `assignee_roles`
+    pub assignee: Option<UserId>,
+    pub assignee_roles: Vec<RoleId>,
+    pub completed: Option<bool>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Only tasks the user gave or is assigned to, directly or through one of `visible_roles`
+    pub visible_to: Option<UserId>,
+    pub visible_roles: Vec<RoleId>,
+}
+
+impl Task {
+    pub fn new(
+        title: impl Into<String>,
+        description: impl Into<String>,
+        deadline: DateTime<Utc>,
+        given_by: UserId,
+    ) -> Self {
+        Self {
+            id: 0,
+            title: title.into(),
+            description: description.into(),
+            completed: false,
+            status: TaskStatus::Todo,
+            deadline,
+            recurrence: None,
+            given_by,
+            reminders: vec![],
+            assigned_users: vec![],
+            assigned_roles: vec![],
+            completed_by: vec![],
+            checklist: vec![],
+            blocked_by: vec![],
+        }
+    }
+
+    /// The deadline passed and the task isn't finished
+    pub fn is_overdue(&self) -> bool {
+        self.deadline < Utc::now() && !self.completed && self.status != TaskStatus::Done
+    }
+
+    /// Whether any of the tasks blocking this one isn't done yet
+    pub fn is_blocked(&self) -> bool {
+        self.blocked_by.iter().any(|b| !b.done)
+    }
+
+    /// Ticked checklist items out of all of them, e.g. "4/7"
+    pub fn checklist_progress(&self) -> Option<String> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self
+            .checklist
+            .iter()
+            .filter(|item| item.done_by.is_some())
+            .count();
+        Some(format!("{}/{}", done, self.checklist.len()))
+    }
+
+    /// Directly assigned users followed by the given members of the assigned roles
+    pub fn assignees(&self, role_members: &HashMap<RoleId, Vec<UserId>>) -> Vec<UserId> {
+        let mut assignees = self.assigned_users.clone();
+        for user in self
+            .assigned_roles
+            .iter()
+            .filter_map(|role| role_members.get(role))
+            .flatten()
+        {
+            if !assignees.contains(user) {
+                assignees.push(*user);
+            }
+        }
+        assignees
+    }
 }
 
 #[derive(Clone, Debug, sqlx::Type)]
@@ -2,14 +2,17 @@ use std::fmt::Display;
 
 #[derive(Debug)]
 pub enum BotError {
-    Serenity(serenity::Error),
+    Serenity(Box<serenity::Error>),
     Db(sqlx::Error),
     ChronoParse(chrono::ParseError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
 }
 
 impl From<serenity::Error> for BotError {
     fn from(value: serenity::Error) -> Self {
-        Self::Serenity(value)
+        Self::Serenity(Box::new(value))
     }
 }
 
@@ -25,6 +28,24 @@ impl From<chrono::ParseError> for BotError {
     }
 }
 
+impl From<std::io::Error> for BotError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for BotError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl From<csv::Error> for BotError {
+    fn from(value: csv::Error) -> Self {
+        Self::Csv(value)
+    }
+}
+
 impl From<sqlx::migrate::MigrateError> for BotError {
     fn from(value: sqlx::migrate::MigrateError) -> Self {
         Self::Db(value.into())
@@ -37,6 +58,9 @@ impl Display for BotError {
             Self::Serenity(e) => format!("serenity: {e}"),
             Self::Db(e) => format!("database: {e}"),
             Self::ChronoParse(e) => format!("chrono: {e}"),
+            Self::Io(e) => format!("io: {e}"),
+            Self::Json(e) => format!("json: {e}"),
+            Self::Csv(e) => format!("csv: {e}"),
         };
 
         write!(f, "{}", s)
@@ -4,15 +4,21 @@ use serenity::{
 };
 
 use crate::{
-    calendar::CalendarHub, components::CommandCtx, database::Db, handler::hourly, log, log_error,
-    log_warn,
+    calendar::CalendarHub,
+    components::CommandCtx,
+    config::Config,
+    database::Db,
+    handler::{hourly, reminder_buttons},
+    log, log_error, log_warn,
 };
 
-use crate::traits::bot_command::BotCommand;
+use crate::traits::{bot_command::BotCommand, Interactable};
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
 async fn add_remove_edit_commands(
@@ -62,14 +68,18 @@ pub struct Handler {
     registered_commands: HashMap<&'static str, Box<dyn BotCommand + Sync + Send>>,
     db: Arc<Db>,
     calendar: Arc<CalendarHub>,
+    config: Arc<Config>,
+    hourly_started: AtomicBool,
 }
 
 impl Handler {
-    pub fn new(db: Db, calendar: CalendarHub) -> Self {
+    pub fn new(db: Db, calendar: CalendarHub, config: Config) -> Self {
         Self {
             registered_commands: HashMap::new(),
             db: Arc::new(db),
             calendar: Arc::new(calendar),
+            config: Arc::new(config),
+            hourly_started: AtomicBool::new(false),
         }
     }
 
@@ -99,14 +109,29 @@ impl Handler {
             interaction: &command,
             db: &self.db.clone(),
             calendars: &self.calendar.clone(),
+            config: &self.config.clone(),
         };
 
-        match comm.run(&new_ctx).await {
-            Ok(_) => (),
-            Err(e) => {
-                log_error!("Error running command {}!: {e}", command.data.name);
-                return;
+        let capability = comm.capability();
+        if !capability.allows(
+            &self.config,
+            command.user.id,
+            command.member.as_deref(),
+            None,
+        ) {
+            log!(
+                "User {} isn't allowed to run {}",
+                command.user.name,
+                command.data.name
+            );
+            if let Err(e) = new_ctx.respond(capability.refusal(), true).await {
+                log_error!("Error refusing command {}!: {e}", command.data.name);
             }
+            return;
+        }
+
+        if let Err(e) = comm.run(&new_ctx).await {
+            log_error!("Error running command {}!: {e}", command.data.name);
         }
     }
 }
@@ -116,7 +141,19 @@ impl EventHandler for Handler {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         match interaction {
             Interaction::Command(command) => self.handle_command(&ctx, command).await,
-            Interaction::Component(_component) => (),
+            Interaction::Component(component) => {
+                if let Err(e) = reminder_buttons::handle(
+                    &ctx,
+                    &component,
+                    &self.db,
+                    &self.config,
+                    &self.calendar,
+                )
+                .await
+                {
+                    log_error!("Error handling reminder button: {e}");
+                }
+            }
             Interaction::Modal(_) => (),
             _ => log_warn!("Unsupported interaction: {:?}", interaction),
         }
@@ -133,10 +170,16 @@ impl EventHandler for Handler {
 
         add_remove_edit_commands(&ctx, &self.registered_commands, &all_global_commands).await;
 
+        // ready fires again after every reconnect, the background job must only run once
+        if self.hourly_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
         let db = self.db.clone();
         let calendar = self.calendar.clone();
+        let config = self.config.clone();
         let http = ctx.http.clone();
 
-        //tokio::spawn(hourly::hourly_task(http, calendar, db));
+        tokio::spawn(hourly::hourly_task(http, calendar, db, config));
     }
 }
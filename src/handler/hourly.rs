@@ -1,56 +1,443 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
-use serenity::all::Http;
+use chrono::Utc;
+use serenity::all::{
+    CreateScheduledEvent, EditScheduledEvent, GuildId, Http, RoleId, ScheduledEventId,
+    ScheduledEventStatus, ScheduledEventType, Timestamp, UserId,
+};
+use serenity::http::StatusCode;
 
-use crate::{aliases::Result, calendar::CalendarHub, database::Db, log, log_error};
+use crate::{
+    aliases::Result,
+    calendar::{CalendarHub, Event},
+    commands::misc,
+    config::Config,
+    database::{Db, DiscordEvent, ReminderGroup, Task},
+    handler::notify::{notify_user, Notification},
+    log, log_error, log_warn,
+    traits::IntoMessage,
+};
 
-static INTERVAL: u64 = 1 * 60 * 60; // hours * minutes * seconds
+static INTERVAL: u64 = 60 * 60; // minutes * seconds
+/// Reminders can be set minutes before a deadline, so they're checked more often than the rest
+static REMINDER_INTERVAL: u64 = 60;
+/// Ticks a reminder is retried on when it reaches the user in no way, e.g. while Discord is down
+const MAX_DELIVERY_ATTEMPTS: i64 = 10;
 
-async fn notify(http: &Http, calendar: &CalendarHub, db: &Db) -> Result {
-    let reminders = db.fetch_event_reminders().await?;
+async fn notify(http: &Http, config: &Config, db: &Db) -> Result {
+    for reminder in db.get_due_reminders().await? {
+        // mark first so a crash or restart can never deliver the same reminder twice
+        db.mark_reminder_sent(reminder.id).await?;
+
+        let delivered = notify_user(
+            http,
+            config,
+            db,
+            reminder.user_id,
+            ReminderGroup::Tasks,
+            &reminder,
+        )
+        .await?;
+        if delivered {
+            continue;
+        }
+
+        // nothing went out, so it's safe to try again on the next tick
+        if db
+            .record_failed_reminder(reminder.id, MAX_DELIVERY_ATTEMPTS)
+            .await?
+        {
+            log_error!(
+                "Gave up on reminder {} for {} after {MAX_DELIVERY_ATTEMPTS} failed deliveries",
+                reminder.id,
+                reminder.user_id
+            );
+        } else {
+            log_warn!("Reminder {} couldn't be delivered, retrying", reminder.id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Members who subscribed to event reminders hear about an event this long before it starts
+const EVENT_REMIND_AHEAD_HOURS: i64 = 24;
+
+async fn remind_events(http: &Http, config: &Config, db: &Db, calendar: &CalendarHub) -> Result {
+    for reminder in db.get_snoozed_event_reminders().await? {
+        db.mark_event_reminder_sent(reminder.id).await?;
+        notify_user(
+            http,
+            config,
+            db,
+            reminder.user_id,
+            ReminderGroup::Events,
+            &reminder,
+        )
+        .await?;
+    }
+
+    let subscribers = db.get_event_subscribers().await?;
+    if subscribers.is_empty() {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let mut events = calendar
+        .get_calendars()
+        .await
+        .iter()
+        .flat_map(|c| c.events.iter().cloned())
+        .collect::<Vec<_>>();
+    events.extend(db.get_custom_events().await?);
+
+    for event in events.iter().filter(|e| {
+        !e.is_cancelled()
+            && e.start > now
+            && e.start < now + chrono::Duration::hours(EVENT_REMIND_AHEAD_HOURS)
+    }) {
+        for user in &subscribers {
+            // claiming first means each member hears about each occurrence once
+            if let Some(reminder) = db.claim_event_reminder(*user, event).await? {
+                notify_user(http, config, db, *user, ReminderGroup::Events, &reminder).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Everyone the task is assigned to, `role_members` has to hold the members of its roles
+fn task_assignees(task: &Task, role_members: &HashMap<RoleId, Vec<UserId>>) -> Vec<UserId> {
+    let mut assignees = task.assigned_users.clone();
+    assignees.extend(
+        task.assigned_roles
+            .iter()
+            .filter_map(|role| role_members.get(role))
+            .flatten(),
+    );
+    assignees.sort_unstable();
+    assignees.dedup();
+    assignees
+}
+
+async fn overdue(http: &Http, config: &Config, db: &Db) -> Result {
+    let newly_overdue = db.get_newly_overdue_tasks().await?;
+    let escalate = match (config.board_channel, config.escalate_after_days) {
+        (Some(_), Some(days)) => {
+            db.get_tasks_to_escalate(chrono::Duration::days(days.into()))
+                .await?
+        }
+        _ => vec![],
+    };
+
+    // the members are listed once for every task of the run, not once per task
+    let mut roles = newly_overdue
+        .iter()
+        .chain(&escalate)
+        .flat_map(|t| t.assigned_roles.iter().copied())
+        .collect::<Vec<_>>();
+    roles.sort_unstable();
+    roles.dedup();
+    let role_members = misc::resolve_role_members(http, config, None, &roles).await?;
+
+    for task in newly_overdue {
+        db.mark_overdue_notified(task.id).await?;
+
+        let assignees = task_assignees(&task, &role_members);
+        let unfinished = assignees
+            .iter()
+            .filter(|u| !task.completed_by.contains(u))
+            .map(|u| format!("<@{}>", u))
+            .collect::<Vec<_>>();
+
+        let notification = Notification {
+            title: format!("Overdue: {}", task.title),
+            description: format!(
+                "This task was due <t:{}:R> and isn't finished yet",
+                task.deadline.timestamp()
+            ),
+        };
+        for user in assignees.iter().filter(|u| !task.completed_by.contains(u)) {
+            notify_user(http, config, db, *user, ReminderGroup::Tasks, &notification).await?;
+        }
+
+        let notification = Notification {
+            title: format!("Overdue: {}", task.title),
+            description: format!(
+                "The task you gave was due <t:{}:f> and isn't finished yet.\nStill working on it: {}",
+                task.deadline.timestamp(),
+                if unfinished.is_empty() {
+                    "nobody".to_owned()
+                } else {
+                    unfinished.join(", ")
+                }
+            ),
+        };
+        notify_user(
+            http,
+            config,
+            db,
+            task.given_by,
+            ReminderGroup::Tasks,
+            &notification,
+        )
+        .await?;
+    }
+
+    let Some(channel) = config.board_channel else {
+        return Ok(());
+    };
+
+    for task in escalate {
+        db.mark_overdue_escalated(task.id).await?;
+
+        let unfinished = task_assignees(&task, &role_members)
+            .into_iter()
+            .filter(|u| !task.completed_by.contains(u))
+            .map(|u| format!("<@{}>", u))
+            .collect::<Vec<_>>();
+        let notification = Notification {
+            title: format!("Still overdue: {} (#{})", task.title, task.id),
+            description: format!(
+                "Given by <@{}>, due <t:{}:R> and still not finished.\nStill working on it: {}",
+                task.given_by,
+                task.deadline.timestamp(),
+                if unfinished.is_empty() {
+                    "nobody".to_owned()
+                } else {
+                    unfinished.join(", ")
+                }
+            ),
+        };
+        if let Err(e) = channel.send_message(http, notification.into_msg()).await {
+            log_error!("Failed to escalate overdue task {}! {e}", task.id);
+        }
+    }
 
     Ok(())
 }
 
 async fn cleanup(db: &Db) -> Result {
+    // the next occurrence has to exist before the finished one gets removed
+    db.generate_recurring_tasks().await?;
     db.delete_completed_expired_tasks().await?;
+    db.delete_past_event_reminders().await?;
     db.delete_expired_custom_events().await
 }
 
-async fn add_calendar_events_as_discord_events(http: &Http, calendar: &CalendarHub) -> Result {
+/// Discord only keeps 100 scheduled events per guild, recurring events would fill that up
+const SYNC_AHEAD_DAYS: i64 = 30;
+/// External scheduled events need an end, events without one get this long
+const DEFAULT_LENGTH_HOURS: i64 = 1;
+/// External scheduled events need a location too
+const NO_LOCATION: &str = "See /events";
+/// The source custom events are synced under, no calendar source can be named this
+const CUSTOM_SOURCE: &str = "";
+
+fn to_discord_event(source: &str, event: &Event) -> DiscordEvent {
+    DiscordEvent {
+        source: source.to_owned(),
+        uid: event.sync_uid(),
+        discord_event_id: ScheduledEventId::default(),
+        summary: misc::truncate(&event.summary, 100),
+        start: event.start,
+        end: event
+            .end
+            .filter(|end| *end > event.start)
+            .unwrap_or(event.start + chrono::Duration::hours(DEFAULT_LENGTH_HOURS)),
+        location: misc::truncate(event.location.as_deref().unwrap_or(NO_LOCATION), 100),
+    }
+}
+
+fn timestamp(time: chrono::DateTime<Utc>) -> Timestamp {
+    Timestamp::from_unix_timestamp(time.timestamp()).unwrap_or_default()
+}
+
+/// The guild event was deleted by hand
+fn is_unknown(e: &serenity::Error) -> bool {
+    matches!(e, serenity::Error::Http(http) if http.status_code() == Some(StatusCode::NOT_FOUND))
+}
+
+async fn create_discord_event(
+    http: &Http,
+    db: &Db,
+    guild: GuildId,
+    event: &Event,
+    mut synced: DiscordEvent,
+) -> Result {
+    let mut create = CreateScheduledEvent::new(
+        ScheduledEventType::External,
+        &synced.summary,
+        timestamp(synced.start),
+    )
+    .end_time(timestamp(synced.end))
+    .location(&synced.location);
+    if let Some(description) = &event.description {
+        create = create.description(misc::truncate(description, 1000));
+    }
+
+    synced.discord_event_id = guild.create_scheduled_event(http, create).await?.id;
+    db.set_discord_event(guild, &synced).await
+}
+
+async fn update_discord_event(
+    http: &Http,
+    db: &Db,
+    guild: GuildId,
+    event: &Event,
+    mut synced: DiscordEvent,
+    id: ScheduledEventId,
+) -> Result {
+    let mut edit = EditScheduledEvent::new()
+        .name(&synced.summary)
+        .start_time(timestamp(synced.start))
+        .end_time(timestamp(synced.end))
+        .location(&synced.location);
+    if let Some(description) = &event.description {
+        edit = edit.description(misc::truncate(description, 1000));
+    }
+
+    match guild.edit_scheduled_event(http, id, edit).await {
+        Ok(_) => {
+            synced.discord_event_id = id;
+            db.set_discord_event(guild, &synced).await
+        }
+        // made again next time
+        Err(e) if is_unknown(&e) => {
+            db.delete_discord_event(guild, &synced.source, &synced.uid)
+                .await
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Mirrors the upcoming calendar and custom events as guild scheduled events,
+/// editing the ones that changed and cancelling the ones that are gone.
+/// A calendar that couldn't be loaded keeps its guild events as they are until it loads again
+async fn add_calendar_events_as_discord_events(
+    http: &Http,
+    config: &Config,
+    db: &Db,
+    calendar: &CalendarHub,
+) -> Result {
+    let Some(guild) = config.events_guild else {
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut loaded = HashSet::from([CUSTOM_SOURCE.to_owned()]);
+    let mut events = vec![];
+    for (source, calendar) in calendar.get_loaded().await {
+        let Some(calendar) = calendar else {
+            continue;
+        };
+        events.extend(calendar.events.iter().map(|e| (source.clone(), e.clone())));
+        loaded.insert(source);
+    }
+    events.extend(
+        db.get_custom_events()
+            .await?
+            .into_iter()
+            .map(|e| (CUSTOM_SOURCE.to_owned(), e)),
+    );
+    let events = events
+        .into_iter()
+        .filter(|(_, e)| {
+            !e.is_cancelled()
+                && e.start > now
+                && e.start < now + chrono::Duration::days(SYNC_AHEAD_DAYS)
+        })
+        .map(|(source, e)| ((source, e.sync_uid()), e))
+        .collect::<HashMap<_, _>>();
+
+    let mut synced = db
+        .get_discord_events(guild)
+        .await?
+        .into_iter()
+        .map(|s| ((s.source.clone(), s.uid.clone()), s))
+        .collect::<HashMap<_, _>>();
+
+    for (key, event) in &events {
+        let wanted = to_discord_event(&key.0, event);
+        let result = match synced.remove(key) {
+            None => create_discord_event(http, db, guild, event, wanted).await,
+            Some(old) if old.differs(&wanted) => {
+                update_discord_event(http, db, guild, event, wanted, old.discord_event_id).await
+            }
+            Some(_) => Ok(()),
+        };
+        if let Err(e) = result {
+            log_error!("Failed to sync event {} to discord! {e}", event.summary);
+        }
+    }
+
+    // whatever is left of a loaded source isn't in its calendar anymore
+    for old in synced.into_values().filter(|s| loaded.contains(&s.source)) {
+        if old.start > now {
+            let cancel = EditScheduledEvent::new().status(ScheduledEventStatus::Canceled);
+            match guild
+                .edit_scheduled_event(http, old.discord_event_id, cancel)
+                .await
+            {
+                Ok(_) => (),
+                Err(e) if is_unknown(&e) => (),
+                Err(e) => {
+                    log_error!("Failed to cancel discord event {}! {e}", old.summary);
+                    continue;
+                }
+            }
+        }
+        // ones that already started end on their own
+        db.delete_discord_event(guild, &old.source, &old.uid)
+            .await?;
+    }
+
     Ok(())
 }
 
-pub async fn hourly_task(http: Arc<Http>, calendar: Arc<CalendarHub>, db: Arc<Db>) {
-    let mut interval = tokio::time::interval(Duration::from_secs(INTERVAL));
+pub async fn hourly_task(
+    http: Arc<Http>,
+    calendar: Arc<CalendarHub>,
+    db: Arc<Db>,
+    config: Arc<Config>,
+) {
+    let mut interval = tokio::time::interval(Duration::from_secs(REMINDER_INTERVAL));
+    let mut ticks: u64 = 0;
     loop {
         interval.tick().await;
 
+        if let Err(e) = notify(&http, &config, &db).await {
+            log_error!("Error notifying users: {e}");
+        }
+
+        if let Err(e) = remind_events(&http, &config, &db, &calendar).await {
+            log_error!("Error reminding users of events: {e}");
+        }
+
+        let hourly = ticks.is_multiple_of(INTERVAL / REMINDER_INTERVAL);
+        ticks += 1;
+        if !hourly {
+            continue;
+        }
+
         log!("Running hourly task");
 
-        match notify(&http, &calendar, &db).await {
-            Ok(()) => log!("Users notified!"),
-            Err(e) => {
-                log_error!("Error notifying users: {e}");
-                return;
-            }
+        match overdue(&http, &config, &db).await {
+            Ok(()) => log!("Overdue tasks announced!"),
+            Err(e) => log_error!("Error announcing overdue tasks: {e}"),
         }
 
-        match add_calendar_events_as_discord_events(&http, &calendar).await {
-            Ok(()) => log!("Events added as discord events!"),
-            Err(e) => {
-                log_error!("Error adding discord events: {e}");
-                return;
-            }
+        match add_calendar_events_as_discord_events(&http, &config, &db, &calendar).await {
+            Ok(()) => log!("Discord events synced!"),
+            Err(e) => log_error!("Error syncing discord events: {e}"),
         }
 
         match cleanup(&db).await {
             Ok(()) => log!("Database cleaned up!"),
-            Err(e) => {
-                log_error!("Error cleaning up the database: {e}");
-                return;
-            }
+            Err(e) => log_error!("Error cleaning up the database: {e}"),
         }
     }
 }
@@ -1,4 +1,7 @@
+#[allow(clippy::module_inception)]
 pub mod handler;
 pub mod hourly;
+pub mod notify;
+pub mod reminder_buttons;
 
 pub use handler::Handler;
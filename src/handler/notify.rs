@@ -0,0 +1,63 @@
+use serenity::all::{CreateEmbed, CreateMessage, Http, UserId};
+
+use crate::{
+    aliases::TypedResult,
+    config::Config,
+    database::{Db, EventReminder, ReminderGroup, ReminderWay},
+    log_error,
+    traits::IntoMessage,
+};
+
+pub struct Notification {
+    pub title: String,
+    pub description: String,
+}
+
+impl IntoMessage for Notification {
+    fn into_msg(&self) -> CreateMessage {
+        CreateMessage::new().embed(
+            CreateEmbed::new()
+                .color(serenity::model::Colour::MEIBE_PINK)
+                .title(&self.title)
+                .description(&self.description),
+        )
+    }
+}
+
+/// Sends `msg` to the user in every way they subscribed to for `group`,
+/// falling back to a direct message when they haven't picked any.
+/// Returns whether it reached them in at least one of them.
+pub async fn notify_user(
+    http: &Http,
+    config: &Config,
+    db: &Db,
+    user_id: UserId,
+    group: ReminderGroup,
+    msg: &impl IntoMessage,
+) -> TypedResult<bool> {
+    let mut ways = db
+        .get_user_event_reminders(user_id)
+        .await?
+        .into_iter()
+        .filter(|r| r.group == group)
+        .collect::<Vec<_>>();
+
+    if ways.is_empty() {
+        ways.push(EventReminder {
+            user_id,
+            way: ReminderWay::DirectMsg,
+            email: None,
+            group,
+        });
+    }
+
+    let mut delivered = false;
+    for way in ways {
+        match way.send(http, config.reminder_channel, msg).await {
+            Ok(()) => delivered = true,
+            Err(e) => log_error!("Failed to notify {} via {}: {e}", user_id, way.way),
+        }
+    }
+
+    Ok(delivered)
+}
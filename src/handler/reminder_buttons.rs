@@ -0,0 +1,414 @@
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use serenity::all::{
+    ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+};
+
+use crate::{
+    aliases::{Result, TypedResult},
+    calendar::{CalendarHub, Event},
+    commands::{events, given_tasks, misc, my_tasks},
+    components::{EventCtx, InteractiveMessage},
+    config::Config,
+    database::{Db, DueEventReminder, ReminderGroup, Task, TaskStatus},
+    handler::notify::{notify_user, Notification},
+    log_warn,
+    traits::{Interactable, IntoEmbed},
+};
+
+// Reminders are delivered long after any collector stopped listening, so everything a button
+// needs is in its custom id: "reminder:<action>:<task id>:<reminder id>",
+// or "event_reminder:<action>:<reminder id>" for events
+const PREFIX: &str = "reminder";
+const EVENT_PREFIX: &str = "event_reminder";
+
+/// Snoozing until tomorrow means this time in the user's timezone
+const TOMORROW_AT: NaiveTime = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+#[derive(Clone, Copy)]
+enum Action {
+    SnoozeHour,
+    SnoozeTomorrow,
+    Done,
+    Open,
+}
+
+impl Action {
+    const ALL: [Self; 4] = [
+        Self::SnoozeHour,
+        Self::SnoozeTomorrow,
+        Self::Done,
+        Self::Open,
+    ];
+    /// There's nothing to mark done about an event
+    const EVENT: [Self; 3] = [Self::SnoozeHour, Self::SnoozeTomorrow, Self::Open];
+
+    fn id(self) -> &'static str {
+        match self {
+            Self::SnoozeHour => "snooze_hour",
+            Self::SnoozeTomorrow => "snooze_tomorrow",
+            Self::Done => "done",
+            Self::Open => "open",
+        }
+    }
+
+    fn button(self, target: Target) -> CreateButton {
+        let (label, style) = match (self, target) {
+            (Self::SnoozeHour, _) => ("Snooze 1h", ButtonStyle::Secondary),
+            (Self::SnoozeTomorrow, _) => ("Snooze until tomorrow 9:00", ButtonStyle::Secondary),
+            (Self::Done, _) => ("Mark done", ButtonStyle::Success),
+            (Self::Open, Target::Task { .. }) => ("Open task", ButtonStyle::Primary),
+            (Self::Open, Target::Event { .. }) => ("Open event", ButtonStyle::Primary),
+        };
+        let id = match target {
+            Target::Task {
+                task_id,
+                reminder_id,
+            } => format!("{PREFIX}:{}:{task_id}:{reminder_id}", self.id()),
+            Target::Event { reminder_id } => format!("{EVENT_PREFIX}:{}:{reminder_id}", self.id()),
+        };
+        CreateButton::new(id).label(label).style(style)
+    }
+}
+
+/// What the delivered reminder was about
+#[derive(Clone, Copy)]
+enum Target {
+    Task { task_id: i64, reminder_id: i64 },
+    Event { reminder_id: i64 },
+}
+
+/// Reads back a custom id made by [`buttons`] or [`event_buttons`]
+fn parse(custom_id: &str) -> Option<(Action, Target)> {
+    let mut parts = custom_id.split(':');
+    let prefix = parts.next()?;
+    if prefix != PREFIX && prefix != EVENT_PREFIX {
+        return None;
+    }
+
+    let action = parts.next()?;
+    let actions: &[Action] = if prefix == PREFIX {
+        &Action::ALL
+    } else {
+        &Action::EVENT
+    };
+    let action = *actions.iter().find(|a| a.id() == action)?;
+    let target = if prefix == PREFIX {
+        let task_id = parts.next()?.parse().ok()?;
+        let reminder_id = parts.next()?.parse().ok()?;
+        Target::Task {
+            task_id,
+            reminder_id,
+        }
+    } else {
+        Target::Event {
+            reminder_id: parts.next()?.parse().ok()?,
+        }
+    };
+    Some((action, target))
+}
+
+pub fn buttons(task_id: i64, reminder_id: i64) -> CreateActionRow {
+    let target = Target::Task {
+        task_id,
+        reminder_id,
+    };
+    CreateActionRow::Buttons(Action::ALL.into_iter().map(|a| a.button(target)).collect())
+}
+
+pub fn event_buttons(reminder_id: i64) -> CreateActionRow {
+    let target = Target::Event { reminder_id };
+    CreateActionRow::Buttons(
+        Action::EVENT
+            .into_iter()
+            .map(|a| a.button(target))
+            .collect(),
+    )
+}
+
+async fn reply(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    msg: EditInteractionResponse,
+) -> Result {
+    interaction.edit_response(ctx, msg).await?;
+    Ok(())
+}
+
+async fn tomorrow_morning(
+    db: &Db,
+    config: &Config,
+    interaction: &ComponentInteraction,
+) -> TypedResult<DateTime<Utc>> {
+    let tz = misc::user_timezone(db, config, interaction.user.id).await?;
+    let tomorrow = Utc::now().with_timezone(&tz).date_naive() + Duration::days(1);
+    Ok(tz
+        .from_local_datetime(&tomorrow.and_time(TOMORROW_AT))
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        // the clocks skipped 9:00 that day
+        .unwrap_or_else(|| Utc::now() + Duration::days(1)))
+}
+
+async fn mark_done(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    db: &Db,
+    config: &Config,
+    mut task: Task,
+) -> TypedResult<&'static str> {
+    let user = interaction.user.id;
+    if task.completed_by.contains(&user) {
+        return Ok("You already marked this task as done!");
+    }
+    if task.is_blocked() {
+        return Ok("This task is still blocked by unfinished tasks!");
+    }
+    if task.status == TaskStatus::Done {
+        return Ok("This task was already approved!");
+    }
+
+    let role_members = match misc::resolve_role_members(
+        &ctx.http,
+        config,
+        interaction.guild_id,
+        &task.assigned_roles,
+    )
+    .await
+    {
+        Ok(members) => Some(misc::merge_role_members(members)),
+        Err(e) => {
+            log_warn!(
+                "Failed to look up the role members of task {}! {e}",
+                task.id
+            );
+            None
+        }
+    };
+    task.completed = db
+        .toggle_task_completion(task.id, user, role_members.as_deref())
+        .await?;
+
+    if task.completed && matches!(task.status, TaskStatus::Todo | TaskStatus::InProgress) {
+        db.set_task_status(
+            task.id,
+            TaskStatus::InReview,
+            role_members.as_deref().unwrap_or_default(),
+            user,
+        )
+        .await?;
+        notify_user(
+            &ctx.http,
+            config,
+            db,
+            task.given_by,
+            ReminderGroup::Tasks,
+            &Notification {
+                title: format!("Ready for review: {}", task.title),
+                description: format!(
+                    "<@{}> moved the task to review, approve it or send it back in /given_tasks",
+                    user
+                ),
+            },
+        )
+        .await?;
+    }
+
+    Ok("Marked as done!")
+}
+
+/// Handles the buttons under a delivered reminder.
+/// Any other component belongs to a live message with its own collector, so it's left alone.
+pub async fn handle(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    db: &Db,
+    config: &Config,
+    calendar: &CalendarHub,
+) -> Result {
+    let Some((action, target)) = parse(&interaction.data.custom_id) else {
+        return Ok(());
+    };
+
+    // the task view answers the press itself
+    if let (Action::Open, Target::Task { task_id, .. }) = (action, target) {
+        return open_task(ctx, interaction, db, config, calendar, task_id).await;
+    }
+
+    // looking through the members of the task's roles can take longer than Discord waits
+    interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    match target {
+        Target::Task {
+            task_id,
+            reminder_id,
+        } => handle_task(ctx, interaction, db, config, action, task_id, reminder_id).await,
+        Target::Event { reminder_id } => {
+            handle_event(ctx, interaction, db, config, calendar, action, reminder_id).await
+        }
+    }
+}
+
+/// Opens the task like /tasks does, as the giver's view for the one who gave it
+async fn open_task(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    db: &Db,
+    config: &Config,
+    calendar: &CalendarHub,
+    task_id: i64,
+) -> Result {
+    let mut reminder = InteractiveMessage::detached((*interaction.message).clone());
+    let mut ctx = EventCtx {
+        discord_ctx: ctx,
+        interaction,
+        msg: &mut reminder,
+        db,
+        calendars: calendar,
+        config,
+    };
+
+    match db.get_tasks(&[task_id]).await?.pop() {
+        None => ctx.respond("This task was deleted!", true).await,
+        Some(task) if task.given_by == interaction.user.id => {
+            given_tasks::command::open_task(&mut ctx, task_id).await
+        }
+        Some(_) => my_tasks::command::open_task(&mut ctx, task_id).await,
+    }
+}
+
+/// The calendar or custom event the reminder was about, if it's still there
+async fn find_event(
+    db: &Db,
+    calendar: &CalendarHub,
+    reminder: &DueEventReminder,
+) -> TypedResult<Option<Event>> {
+    let mut events = calendar
+        .get_calendars()
+        .await
+        .iter()
+        .flat_map(|c| c.events.iter().cloned())
+        .collect::<Vec<_>>();
+    events.extend(db.get_custom_events().await?);
+    Ok(events
+        .into_iter()
+        .find(|e| e.sync_uid() == reminder.uid && e.start == reminder.start))
+}
+
+async fn handle_event(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    db: &Db,
+    config: &Config,
+    calendar: &CalendarHub,
+    action: Action,
+    reminder_id: i64,
+) -> Result {
+    let user = interaction.user.id;
+    let Some(reminder) = db.get_event_reminder(reminder_id, user).await? else {
+        let msg = EditInteractionResponse::new().content("This event already started!");
+        return reply(ctx, interaction, msg).await;
+    };
+
+    let content = match action {
+        Action::SnoozeHour | Action::SnoozeTomorrow => {
+            let until = match action {
+                Action::SnoozeHour => Utc::now() + Duration::hours(1),
+                _ => tomorrow_morning(db, config, interaction).await?,
+            };
+            if until >= reminder.start {
+                "The event starts before then!".to_owned()
+            } else if db.snooze_event_reminder(reminder_id, user, until).await? {
+                format!("I'll remind you again <t:{}:R>", until.timestamp())
+            } else {
+                "This event already started!".to_owned()
+            }
+        }
+        Action::Done => unreachable!("event reminders have no done button"),
+        Action::Open => {
+            let embed = match find_event(db, calendar, &reminder).await? {
+                Some(event) => {
+                    events::embed::Embed::format_event(events::embed::Embed::into_embed(), &event)
+                }
+                // the calendar couldn't be loaded or the event was taken out of it
+                None => events::embed::Embed::into_embed()
+                    .title(&reminder.summary)
+                    .field(
+                        "When",
+                        format!("<t:{}:f>", reminder.start.timestamp()),
+                        false,
+                    ),
+            };
+            let msg = EditInteractionResponse::new()
+                .content("See every event in /events")
+                .embed(embed);
+            return reply(ctx, interaction, msg).await;
+        }
+    };
+
+    reply(
+        ctx,
+        interaction,
+        EditInteractionResponse::new().content(content),
+    )
+    .await
+}
+
+async fn handle_task(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    db: &Db,
+    config: &Config,
+    action: Action,
+    task_id: i64,
+    reminder_id: i64,
+) -> Result {
+    let task = match db.get_tasks(&[task_id]).await?.pop() {
+        None => {
+            let msg = EditInteractionResponse::new().content("This task was deleted!");
+            return reply(ctx, interaction, msg).await;
+        }
+        Some(t) => t,
+    };
+
+    let user = interaction.user.id;
+    let roles = misc::user_roles(&ctx.http, config, interaction.member.as_ref(), user).await?;
+    let assigned = task.assigned_users.contains(&user)
+        || task.assigned_roles.iter().any(|r| roles.contains(r));
+    if !assigned {
+        let msg = EditInteractionResponse::new().content("This task isn't assigned to you!");
+        return reply(ctx, interaction, msg).await;
+    }
+
+    let content = match action {
+        Action::SnoozeHour | Action::SnoozeTomorrow => {
+            let until = match action {
+                Action::SnoozeHour => Utc::now() + Duration::hours(1),
+                _ => tomorrow_morning(db, config, interaction).await?,
+            };
+            if db.snooze_reminder(reminder_id, user, until).await? {
+                format!("I'll remind you again <t:{}:R>", until.timestamp())
+            } else {
+                "This reminder was deleted!".to_owned()
+            }
+        }
+        Action::Done => mark_done(ctx, interaction, db, config, task)
+            .await?
+            .to_owned(),
+        Action::Open => unreachable!("opening a task is answered before deferring"),
+    };
+
+    reply(
+        ctx,
+        interaction,
+        EditInteractionResponse::new().content(content),
+    )
+    .await
+}
@@ -1,11 +1,17 @@
 use serenity::{all::GatewayIntents, Client};
 
 use crate::{
+    calendar::CalendarSource,
     commands::{
         add_custom_event::command::AddEventCommand, add_task::AddTaskCommand,
-        events::command::EventsCommand, given_tasks::GivenTasksCommand, my_tasks::MyTasksCommand,
-        remind_events::RemindEventsCommand, summaries::command::SummariesCommand, Ping,
+        calendar_preview::CalendarPreviewCommand, events::command::EventsCommand,
+        export_tasks::ExportTasksCommand, given_tasks::GivenTasksCommand,
+        import_tasks::ImportTasksCommand, my_tasks::MyTasksCommand,
+        remind_events::RemindEventsCommand, stats::StatsCommand,
+        summaries::command::SummariesCommand, tasks::TasksCommand,
+        timezone::command::TimezoneCommand, Ping,
     },
+    config::Config,
     database::Db,
     handler::Handler,
 };
@@ -14,10 +20,12 @@ pub mod aliases;
 pub mod calendar;
 pub mod commands;
 pub mod components;
+pub mod config;
 pub mod database;
 pub mod error;
 pub mod handler;
 pub mod log;
+pub mod permissions;
 pub mod traits;
 
 #[tokio::main]
@@ -33,15 +41,25 @@ async fn main() {
 
     let intents = GatewayIntents::non_privileged() | GatewayIntents::MESSAGE_CONTENT;
 
-    let url = match std::fs::read_to_string("calendar.secret") {
-        Ok(u) => u,
+    let config = match Config::load("config.json") {
+        Ok(c) => c,
         Err(e) => {
-            log_error!("Failed to read calendar.secret! {e}");
-            return;
+            log_warn!("Failed to read config.json, using defaults! {e}");
+            Config::default()
         }
     };
 
-    let hub = calendar::CalendarHub::new(url).await;
+    let mut sources = config.calendars.clone();
+    if sources.is_empty() {
+        match std::fs::read_to_string("calendar.secret") {
+            Ok(url) => sources.push(CalendarSource::legacy(url.trim())),
+            Err(e) => log_warn!("No calendars configured and failed to read calendar.secret! {e}"),
+        }
+    }
+
+    let hub = calendar::CalendarHub::new(sources).await;
+    let events = EventsCommand::new(hub.names());
+    let calendar_preview = CalendarPreviewCommand::new(hub.names());
     let db = match Db::new("bot_db.sqlite", 5).await {
         Ok(db) => db,
         Err(e) => {
@@ -50,15 +68,21 @@ async fn main() {
         }
     };
 
-    let handler = Handler::new(db, hub)
+    let handler = Handler::new(db, hub, config)
         .register_command("ping", Ping)
-        .register_command("events", EventsCommand)
+        .register_command("events", events)
         .register_command("add_task", AddTaskCommand)
         .register_command("my_tasks", MyTasksCommand)
         .register_command("add_event", AddEventCommand)
         .register_command("summaries", SummariesCommand)
         .register_command("reminders", RemindEventsCommand)
-        .register_command("given_tasks", GivenTasksCommand);
+        .register_command("given_tasks", GivenTasksCommand)
+        .register_command("timezone", TimezoneCommand)
+        .register_command("tasks", TasksCommand)
+        .register_command("export_tasks", ExportTasksCommand)
+        .register_command("import_tasks", ImportTasksCommand)
+        .register_command("stats", StatsCommand)
+        .register_command("calendar_preview", calendar_preview);
 
     let mut client = match Client::builder(token, intents).event_handler(handler).await {
         Ok(c) => {
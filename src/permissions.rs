@@ -0,0 +1,55 @@
+use serde::Deserialize;
+use serenity::all::{Member, UserId};
+
+use crate::config::Config;
+
+/// What a user needs to be allowed to run a command or press a button.
+/// Admins can do everything, board members everything but admin things.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    Everyone,
+    Admin,
+    Board,
+    /// The one who gave the task the message is showing
+    TaskOwner,
+}
+
+impl Capability {
+    /// `owner` is the giver of the task being acted on, if there is one
+    pub fn allows(
+        self,
+        config: &Config,
+        user: UserId,
+        member: Option<&Member>,
+        owner: Option<UserId>,
+    ) -> bool {
+        let has_role = |capability: Capability| {
+            member.is_some_and(|m| {
+                config
+                    .roles
+                    .get(&capability)
+                    .is_some_and(|roles| m.roles.iter().any(|r| roles.contains(r)))
+            })
+        };
+        let admin = member.is_some_and(|m| m.permissions.is_some_and(|p| p.administrator()))
+            || has_role(Self::Admin);
+
+        match self {
+            Self::Everyone => true,
+            Self::Admin => admin,
+            Self::Board => admin || has_role(Self::Board),
+            Self::TaskOwner => admin || owner.is_none_or(|owner| owner == user),
+        }
+    }
+
+    /// Told to a user who isn't allowed
+    pub fn refusal(self) -> &'static str {
+        match self {
+            Self::Everyone => "You can't do that!",
+            Self::Admin => "Only admins can do that!",
+            Self::Board => "Only board members can do that!",
+            Self::TaskOwner => "Only the one who gave this task can do that!",
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use serenity::{all::CreateCommand, async_trait};
 
-use crate::{aliases::Result, components::CommandCtx};
+use crate::{aliases::Result, components::CommandCtx, permissions::Capability};
 
 #[async_trait]
 pub trait BotCommand {
     fn register(&self, command: CreateCommand) -> CreateCommand;
     async fn run(&self, ctx: &CommandCtx) -> Result;
+
+    /// What the user needs to run the command
+    fn capability(&self) -> Capability {
+        Capability::Everyone
+    }
 }
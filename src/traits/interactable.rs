@@ -2,8 +2,8 @@ use std::time::Duration;
 
 use serenity::{
     all::{
-        Builder, Context, CreateInteractionResponse, EditMessage, InteractionId, Message,
-        MessageCollector, UserId,
+        Builder, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+        EditInteractionResponse, EditMessage, InteractionId, Message, MessageCollector, UserId,
     },
     futures::StreamExt,
 };
@@ -36,6 +36,27 @@ pub trait Interactable<'ctx> {
         }
     }
 
+    /// Tells Discord the response will take a while, send it with [`Self::finish_deferred`]
+    fn defer(&self, ephemeral: bool) -> impl Future<Output = Result> {
+        async move {
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(ephemeral),
+            )
+            .execute(self.discord_ctx(), self.id_token())
+            .await?;
+            Ok(())
+        }
+    }
+
+    fn finish_deferred(&self, response: EditInteractionResponse) -> impl Future<Output = Result> {
+        async move {
+            response
+                .execute(self.discord_ctx(), self.id_token().1)
+                .await?;
+            Ok(())
+        }
+    }
+
     fn respond_and_get_response(
         &self,
         msg: impl IntoResponse,
@@ -54,14 +75,14 @@ pub trait Interactable<'ctx> {
                 .get_original_interaction_response(token)
                 .await?;
 
-            let mut collector = MessageCollector::new(&discord_ctx)
+            let mut collector = MessageCollector::new(discord_ctx)
                 .channel_id(iteraction_response.channel_id)
                 .author_id(from)
                 .timeout(Duration::from_secs(60))
                 .filter(move |msg| {
                     msg.referenced_message
                         .as_ref()
-                        .map_or(false, |m| m.id == iteraction_response.id)
+                        .is_some_and(|m| m.id == iteraction_response.id)
                 })
                 .stream();
 
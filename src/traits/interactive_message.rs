@@ -6,12 +6,16 @@ use serenity::{
 use crate::{
     aliases::Result,
     components::{CommandCtx, EventCtx, State},
+    permissions::Capability,
+    traits::StateTrait,
 };
 
 #[async_trait]
 pub trait InteractiveMessageTrait {
-    type State;
+    type State: StateTrait + Send + Sync + 'static;
     fn into_msg() -> CreateInteractionResponseMessage;
+    /// What the user needs to press the component with this id
+    fn capability(custom_id: &str) -> Capability;
     async fn with_embeds_command(ctx: &CommandCtx, state: &State) -> Vec<CreateEmbed>;
     async fn with_embeds_event(ctx: &EventCtx) -> Vec<CreateEmbed>;
     async fn handle_event(ctx: &mut EventCtx) -> Result;
@@ -1,6 +1,7 @@
 use serenity::all::CreateMessage;
 
 pub trait IntoMessage {
+    #[allow(clippy::wrong_self_convention)]
     fn into_msg(&self) -> CreateMessage;
 }
 
@@ -1,6 +1,7 @@
 use serenity::all::CreateInteractionResponseMessage;
 
 pub trait IntoResponse {
+    #[allow(clippy::wrong_self_convention)]
     fn into_response(&self) -> CreateInteractionResponseMessage;
 }
 
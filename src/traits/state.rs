@@ -1,10 +1,15 @@
-use serenity::async_trait;
+use serenity::{all::UserId, async_trait};
 
 use crate::{aliases::TypedResult, components::CommandCtx};
 
 #[async_trait]
 pub trait StateTrait: Clone {
     async fn init(ctx: &CommandCtx) -> TypedResult<Self>;
+
+    /// Giver of the task the message is showing, checked for `Capability::TaskOwner`
+    fn task_owner(&self) -> Option<UserId> {
+        None
+    }
 }
 
 #[async_trait]